@@ -1,158 +1,534 @@
 mod ball;
 mod board;
+mod bolt;
 mod wall;
 mod brick;
-mod text;
+mod capsule;
+mod guard;
+mod held;
+mod hud;
+mod rng;
+mod state;
+pub mod campaign;
+pub mod canvas;
+pub mod config;
+pub mod keymap;
+pub mod level;
+pub mod simulation;
+pub mod text;
 
-use ball::Ball;
-use board::Board;
-use wall::Wall;
-use brick::Brick;
-use text::{Text, HorizontalAlign, VerticalAlign};
+use campaign::Campaign;
+use canvas::{Compositor, Viewport};
+use config::Config;
+use guard::TerminalGuard;
+use held::HeldKeys;
+use hud::{Hud, HUD_HEIGHT};
+use simulation::{Input, Simulation};
+use state::GameState;
+use text::{HorizontalAlign, Position, Text, VerticalAlign};
 use crossterm::{
-    cursor,
-    event::{poll, read, Event, KeyCode},
-    style::{self},
+    event::{poll, read, Event, KeyEventKind},
+    style::{self, Stylize},
     terminal::{self, WindowSize},
-    QueueableCommand,
 };
-use std::io::{self, stdout, Write};
+use std::io::{self, stdout};
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+/// The most simulation steps run back to back before giving up on catching
+/// up, e.g. after the process was suspended.
+const MAX_STEPS_PER_FRAME: u32 = 5;
+
+/// Rows and columns of the playfield. It is the same whatever the size of
+/// the terminal, so that levels play the same everywhere, and small enough
+/// that with the HUD and border it fits a standard 80x24 terminal.
+const ARENA: GameDimension = GameDimension(19, 70);
+
+/// The HUD with the playfield below it, as laid out in the terminal.
+const SCREEN: GameDimension = GameDimension(HUD_HEIGHT + ARENA.0, ARENA.1);
+
+/// Smallest terminal, in rows and columns, that fits the screen and a
+/// border around it.
+const MIN_SIZE: GameDimension = GameDimension(SCREEN.0 + 2, SCREEN.1 + 2);
+const _: () = assert!(MIN_SIZE.0 <= 24 && MIN_SIZE.1 <= 80);
+
+/// Slack for floating point error when deciding whether two boxes touch.
+const EPSILON: f64 = 1e-9;
+
+/// Rows and columns of the area the game is played in.
 #[derive(Clone, Copy)]
-pub struct GameDimension(u16, u16);
+pub struct GameDimension(pub u16, pub u16);
+
+/// Bounding box `(x1, y1, x2, y2)` in world units, where the terminal cell
+/// at column `c` and row `r` spans `c..c + 1` and `r..r + 1`.
+pub struct ObjectCoordinates(pub f64, pub f64, pub f64, pub f64);
 
-pub struct ObjectCoordinates(u16, u16, u16, u16);
+impl ObjectCoordinates {
+    /// Whether the boxes overlap. Boxes that only share an edge count too.
+    pub fn overlaps(&self, other: &ObjectCoordinates) -> bool {
+        let ObjectCoordinates(ax1, ay1, ax2, ay2) = *self;
+        let ObjectCoordinates(bx1, by1, bx2, by2) = *other;
 
-pub struct Vector(f64, f64);
+        let x1 = ax1.max(bx1);
+        let x2 = ax2.min(bx2);
+        let y1 = ay1.max(by1);
+        let y2 = ay2.min(by2);
+
+        x1 <= x2 + EPSILON && y1 <= y2 + EPSILON
+    }
+
+    /// The fraction of `motion` after which this box, moving by `motion`,
+    /// first touches `other`.
+    ///
+    /// `None` if they never meet during the motion, or already overlap before
+    /// it starts.
+    pub fn time_of_impact(&self, motion: &Vector, other: &ObjectCoordinates) -> Option<f64> {
+        let ObjectCoordinates(ax1, ay1, ax2, ay2) = *self;
+        let ObjectCoordinates(bx1, by1, bx2, by2) = *other;
+        let Vector(dx, dy) = *motion;
+
+        let (x_entry, x_exit) = sweep_axis(ax1, ax2, bx1, bx2, dx)?;
+        let (y_entry, y_exit) = sweep_axis(ay1, ay2, by1, by2, dy)?;
+
+        let entry = x_entry.max(y_entry);
+        let exit = x_exit.min(y_exit);
+
+        if entry < exit && (-EPSILON..=1.0).contains(&entry) {
+            Some(entry.max(0.0))
+        } else {
+            None
+        }
+    }
+
+    /// The outward normal of the face (or corner) of this box that `other`,
+    /// moving with `velocity`, is pressing against.
+    ///
+    /// The face is the one along which the boxes overlap the least, as long
+    /// as `other` is moving into it. When both axes are equally shallow the
+    /// hit is on a corner and the normal points diagonally out of it.
+    pub fn face_normal(&self, other: &ObjectCoordinates, velocity: &Vector) -> Vector {
+        let ObjectCoordinates(ax1, ay1, ax2, ay2) = *self;
+        let ObjectCoordinates(bx1, by1, bx2, by2) = *other;
+        let Vector(vx, vy) = *velocity;
+
+        // Zero when only touching, negative when apart
+        let x_overlap = ax2.min(bx2) - ax1.max(bx1);
+        let y_overlap = ay2.min(by2) - ay1.max(by1);
+
+        // Which side of this box the other one is on
+        let side = |a1: f64, a2: f64, b1: f64, b2: f64, v: f64| {
+            let offset = (b1 + b2) - (a1 + a2);
+            if offset.abs() > EPSILON {
+                offset.signum()
+            } else if v != 0.0 {
+                // Dead centre, so it came from where it is moving away from
+                -v.signum()
+            } else {
+                1.0
+            }
+        };
+        let sx = side(ax1, ax2, bx1, bx2, vx);
+        let sy = side(ay1, ay2, by1, by2, vy);
+
+        let x_approaching = vx * sx < 0.0;
+        let y_approaching = vy * sy < 0.0;
+
+        if (x_overlap - y_overlap).abs() <= EPSILON && x_approaching && y_approaching {
+            return Vector(sx, sy).normalized();
+        }
+
+        let x_face = if x_approaching != y_approaching {
+            x_approaching
+        } else {
+            x_overlap < y_overlap
+        };
+
+        if x_face {
+            Vector(sx, 0.0)
+        } else {
+            Vector(0.0, sy)
+        }
+    }
+}
+
+/// When the span `a1..a2`, moving by `d`, starts and stops overlapping
+/// `b1..b2`, as fractions of `d`.
+fn sweep_axis(a1: f64, a2: f64, b1: f64, b2: f64, d: f64) -> Option<(f64, f64)> {
+    if d == 0.0 {
+        // Not moving along this axis, so they have to overlap already
+        if a1 < b2 - EPSILON && b1 < a2 - EPSILON {
+            Some((f64::NEG_INFINITY, f64::INFINITY))
+        } else {
+            None
+        }
+    } else if d > 0.0 {
+        Some(((b1 - a2) / d, (b2 - a1) / d))
+    } else {
+        Some(((b2 - a1) / d, (b1 - a2) / d))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Vector(pub f64, pub f64);
+
+impl Vector {
+    pub fn magnitude(&self) -> f64 {
+        self.0.hypot(self.1)
+    }
+
+    pub fn normalized(&self) -> Vector {
+        let magnitude = self.magnitude();
+        if magnitude == 0.0 {
+            return *self;
+        }
+        Vector(self.0 / magnitude, self.1 / magnitude)
+    }
+}
 
 pub struct Game {
-    ball: Ball,
-    board: Board,
-    walls: [Wall; 3],
-    bricks: Vec<Brick>,
+    sim: Simulation,
+    hud: Hud,
+    state: GameState,
+    /// Launches and shots asked for since the last step.
+    input: Input,
+    /// Directions the player is holding the paddle in.
+    held: HeldKeys,
+    /// Size of the screen the game draws to, see `SCREEN`.
     dim: GameDimension,
+    /// Size of the terminal the screen is centred in.
+    terminal: GameDimension,
+    campaign: Campaign,
+    config: Config,
+}
+
+/// Somewhere game objects can be drawn to, one styled symbol at a time.
+pub trait Canvas {
+    fn put(&mut self, x: u16, y: u16, symbol: style::StyledContent<&str>) -> io::Result<()>;
+    fn present(&mut self) -> io::Result<()>;
 }
 
 pub trait GameObject {
-    fn fill_object(&self, symbol: style::StyledContent<&str>) -> io::Result<()>;
-    fn draw_object(&self) -> io::Result<()>;
-    fn clear_object(&self) -> io::Result<()>;
-    fn move_object(&mut self) -> io::Result<()>;
+    fn fill_object(&self, canvas: &mut dyn Canvas, symbol: style::StyledContent<&str>) -> io::Result<()>;
+    fn draw_object(&self, canvas: &mut dyn Canvas) -> io::Result<()>;
+    fn move_object(&mut self);
 }
 
 pub trait MoveCommand {
-    fn move_right(&mut self) -> io::Result<()>;
-    fn move_left(&mut self) -> io::Result<()>;
-    fn move_up(&mut self) -> io::Result<()>;
-    fn move_down(&mut self) -> io::Result<()>;
+    fn move_right(&mut self);
+    fn move_left(&mut self);
+    fn move_up(&mut self);
+    fn move_down(&mut self);
 }
 
 pub trait Collidable {
     fn get_velocity(&self) -> Vector;
-    fn get_normal(&self, other: &dyn Collidable) -> &Vector;
+    /// The surface normal of this object where `other` is hitting it.
+    fn get_normal(&self, other: &dyn Collidable) -> Vector {
+        self.get_coordinates()
+            .face_normal(&other.get_coordinates(), &other.get_velocity())
+    }
+    /// Direction to send `other` off in after hitting this object, for
+    /// surfaces that steer rather than mirror what bounces off them.
+    fn get_deflection(&self, _other: &dyn Collidable) -> Option<Vector> {
+        None
+    }
     fn get_coordinates(&self) -> ObjectCoordinates;
-    fn has_collision(&self, other: &dyn Collidable) -> bool;
+    fn has_collision(&self, other: &dyn Collidable) -> bool {
+        self.get_coordinates().overlaps(&other.get_coordinates())
+    }
+    /// The fraction of `motion` after which `other`, moving by `motion`,
+    /// runs into this object.
+    fn time_of_impact(&self, other: &dyn Collidable, motion: &Vector) -> Option<f64> {
+        other
+            .get_coordinates()
+            .time_of_impact(motion, &self.get_coordinates())
+    }
 }
 
 pub trait GamePhysics {
-    fn update_object(&mut self) -> io::Result<()>;
-    fn handle_collision(&mut self, other: &dyn Collidable) -> io::Result<()>;
+    fn update_object(&mut self);
+    fn handle_collision(&mut self, other: &dyn Collidable);
 }
 
 impl Game {
-    pub fn new() -> Self {
-        let WindowSize { rows, columns, .. } = terminal::window_size().unwrap();
-        let dim = GameDimension(rows, columns);
-
-        Game {
-            dim,
-            ball: Ball::new(dim.clone()),
-            board: Board::new(dim.clone()),
-            walls: [
-                Wall::new(dim.clone(), wall::Direction::Left),
-                Wall::new(dim.clone(), wall::Direction::Right),
-                Wall::new(dim.clone(), wall::Direction::Top),
-                // Wall::new(dim.clone(), wall::Direction::Bottom),
-            ],
-            bricks: (0..dim.1).filter(|x| x % 4 != 0).map(|x| Brick::new(x, 2)).collect(),
-        }
+    pub fn new(campaign: Campaign, config: Config) -> io::Result<Self> {
+        let WindowSize { rows, columns, .. } = terminal::window_size()?;
+
+        Ok(Game {
+            dim: SCREEN,
+            terminal: GameDimension(rows, columns),
+            sim: Simulation::new(ARENA, campaign.level(), &config),
+            hud: Hud::new(SCREEN),
+            state: GameState::Title,
+            input: Input::default(),
+            held: HeldKeys::new(),
+            campaign,
+            config,
+        })
     }
 
-    fn setup(&self) -> io::Result<()> {
-        let mut stdout = stdout();
+    /// Whether the terminal is too small to fit the screen in.
+    fn is_too_small(&self) -> bool {
+        self.terminal.0 < MIN_SIZE.0 || self.terminal.1 < MIN_SIZE.1
+    }
 
-        stdout
-            .queue(terminal::EnterAlternateScreen)?
-            .queue(cursor::Hide)?;
+    /// Start over from the first level with a fresh world.
+    fn restart(&mut self) {
+        self.campaign.rewind();
+        self.sim = Simulation::new(self.sim.dim(), self.campaign.level(), &self.config);
+        self.input = Input::default();
+    }
 
-        terminal::enable_raw_mode();
-        Ok(())
+    /// Move on to the next level of the campaign, keeping score and lives.
+    /// Returns false once the last level has been played.
+    fn next_level(&mut self) -> bool {
+        if !self.campaign.advance() {
+            return false;
+        }
+        self.sim.load_level(self.campaign.level());
+        self.input = Input::default();
+        true
     }
 
     pub fn run(&mut self) -> io::Result<()> {
-        self.setup()?;
-
-        let texts = [
-            Text::new("Top Left", text::Position(HorizontalAlign::Left, VerticalAlign::Top), self.dim.clone()),
-            Text::new("Top Centre", text::Position(HorizontalAlign::Centre, VerticalAlign::Top), self.dim.clone()),
-            Text::new("Top Right", text::Position(HorizontalAlign::Right, VerticalAlign::Top), self.dim.clone()),
-            Text::new("Centre Left", text::Position(HorizontalAlign::Left, VerticalAlign::Centre), self.dim.clone()),
-            Text::new("Centre Centre", text::Position(HorizontalAlign::Centre, VerticalAlign::Centre), self.dim.clone()),
-            Text::new("Centre Right", text::Position(HorizontalAlign::Right, VerticalAlign::Centre), self.dim.clone()),
-            Text::new("Bottom Left", text::Position(HorizontalAlign::Left, VerticalAlign::Bottom), self.dim.clone()),
-            Text::new("Bottom Centre", text::Position(HorizontalAlign::Centre, VerticalAlign::Bottom), self.dim.clone()),
-            Text::new("Bottom Right", text::Position(HorizontalAlign::Right, VerticalAlign::Bottom), self.dim.clone())
-        ];
-
-
-        loop {
-            for t in &texts {
-                t.draw_object();
-            }
-            self.ball.draw_object();
-            for w in &self.walls {
-                w.draw_object();
+        let _terminal = TerminalGuard::enter()?;
+
+        let mut canvas = Compositor::new(stdout(), self.terminal.1, self.terminal.0);
+
+        let tick = Duration::from_secs(1) / self.config.tick_rate;
+        let mut accumulator = Duration::ZERO;
+        let mut last = Instant::now();
+
+        while self.state != GameState::Quit {
+            if poll(tick.saturating_sub(accumulator))? {
+                match read()? {
+                    Event::Key(event) if event.kind == KeyEventKind::Release => {
+                        if let Some(action) = self.config.keys.action(event.code) {
+                            self.held.release(action, event.code);
+                        }
+                    }
+                    Event::Key(event) => {
+                        let state = self.state;
+                        self.state = state.on_key(self, event.code);
+                    }
+                    Event::FocusLost => {
+                        // Releases won't arrive while another window has focus
+                        self.held.release_all();
+                        self.state = self.state.on_focus_lost();
+                    }
+                    Event::Resize(columns, rows) => {
+                        self.terminal = GameDimension(rows, columns);
+                        canvas.resize(columns, rows)?;
+                        if self.is_too_small() {
+                            // Don't play on out of sight
+                            self.state = self.state.on_focus_lost();
+                        }
+                    }
+                    _ => (),
+                }
             }
-            for b in &self.bricks {
-                b.draw_object();
+
+            let now = Instant::now();
+            accumulator += now - last;
+            last = now;
+
+            if accumulator < tick {
+                continue;
             }
-            self.board.draw_object();
-            if poll(Duration::from_millis(40))? {
-                match read()? {
-                    Event::Key(event) if event.code == KeyCode::Left => self.board.move_left(),
-                    Event::Key(event) if event.code == KeyCode::Right => self.board.move_right(),
-                    Event::Key(event) if event.code == KeyCode::Esc => break,
-                    _ => Ok(()),
-                };
+
+            let mut steps = 0;
+            while accumulator >= tick && steps < MAX_STEPS_PER_FRAME {
+                let state = self.state;
+                self.state = state.update(self);
+                accumulator -= tick;
+                steps += 1;
             }
-            for b in &mut self.bricks {
-                b.handle_collision(&self.ball);
-                self.ball.handle_collision(b);
+            if accumulator >= tick {
+                // Too far behind, drop the backlog instead of fast-forwarding
+                accumulator = Duration::ZERO;
             }
-            for w in &self.walls {
-                self.ball.handle_collision(w);
+
+            if self.is_too_small() {
+                self.draw_too_small(&mut canvas)?;
+            } else {
+                self.hud
+                    .update(&self.sim, self.campaign.number(), self.config.tick_rate);
+
+                // Centred, with the border just around it
+                let x = (self.terminal.1 - SCREEN.1) / 2;
+                let y = (self.terminal.0 - SCREEN.0) / 2;
+                let color = self.config.colors.border;
+                draw_border(&mut canvas, x - 1, y - 1, SCREEN.1 + 2, SCREEN.0 + 2, color)?;
+                self.state
+                    .draw(self, &mut Viewport::new(&mut canvas, x, y))?;
             }
-            self.ball.handle_collision(&self.board);
-            self.ball.update_object();
-            // sleep(Duration::from_millis(5));
+            canvas.present()?;
         }
 
-        self.clear()?;
-
         Ok(())
     }
 
-    fn clear(&mut self) -> io::Result<()> {
-        let mut stdout = stdout();
+    /// Ask for a bigger terminal instead of drawing the game.
+    fn draw_too_small(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
+        let message = format!(
+            "Terminal too small\n\nNeed {}x{}\nHave {}x{}",
+            MIN_SIZE.1, MIN_SIZE.0, self.terminal.1, self.terminal.0
+        );
+        Text::new(
+            &message,
+            Position(HorizontalAlign::Centre, VerticalAlign::Centre),
+            self.terminal,
+        )
+        .draw_object(canvas)
+    }
+}
 
-        stdout
-            .queue(cursor::Show)?
-            .queue(terminal::LeaveAlternateScreen)?;
+/// A box `width` x `height` cells with its top left corner at `x`, `y`.
+fn draw_border(
+    canvas: &mut dyn Canvas,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    color: style::Color,
+) -> io::Result<()> {
+    let right = x + width - 1;
+    let bottom = y + height - 1;
 
-        let _ = stdout.flush()?;
+    canvas.put(x, y, "┌".with(color))?;
+    canvas.put(right, y, "┐".with(color))?;
+    canvas.put(x, bottom, "└".with(color))?;
+    canvas.put(right, bottom, "┘".with(color))?;
+    for i in x + 1..right {
+        canvas.put(i, y, "─".with(color))?;
+        canvas.put(i, bottom, "─".with(color))?;
+    }
+    for j in y + 1..bottom {
+        canvas.put(x, j, "│".with(color))?;
+        canvas.put(right, j, "│".with(color))?;
+    }
+    Ok(())
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single cell with its top left corner at `x`, `y`.
+    fn cell(x: f64, y: f64) -> ObjectCoordinates {
+        ObjectCoordinates(x, y, x + 1.0, y + 1.0)
+    }
+
+    #[test]
+    fn builtin_levels_fit_the_arena() {
+        Campaign::builtin();
+    }
+
+    #[test]
+    fn fast_ball_cannot_skip_a_thin_brick() {
+        let ball = cell(5.0, 10.0);
+        let brick = cell(5.0, 3.0);
+
+        // Twenty cells in one step, straight through where the brick is
+        let time = ball.time_of_impact(&Vector(0.0, -20.0), &brick);
+        assert_eq!(time, Some(0.3));
+
+        // Just beside it
+        assert_eq!(
+            ball.time_of_impact(&Vector(0.0, -20.0), &cell(6.5, 3.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn touching_and_moving_away_is_no_impact() {
+        let ball = cell(5.0, 4.0);
+        let brick = cell(5.0, 3.0);
+
+        assert_eq!(ball.time_of_impact(&Vector(0.0, 1.0), &brick), None);
+        assert_eq!(ball.time_of_impact(&Vector(0.5, 0.5), &brick), None);
+        // Still an impact straight away when moving into it
+        assert_eq!(ball.time_of_impact(&Vector(0.0, -1.0), &brick), Some(0.0));
+    }
+
+    #[test]
+    fn motion_along_one_axis() {
+        let wall = ObjectCoordinates(3.0, 0.0, 4.0, 10.0);
+
+        let time = cell(0.0, 5.0)
+            .time_of_impact(&Vector(3.0, 0.0), &wall)
+            .unwrap();
+        assert!((time - 2.0 / 3.0).abs() < EPSILON);
+
+        // Out of reach, or sliding along the end of it
+        assert_eq!(
+            cell(0.0, 12.0).time_of_impact(&Vector(3.0, 0.0), &wall),
+            None
+        );
+        assert_eq!(
+            cell(0.0, 10.0).time_of_impact(&Vector(3.0, 0.0), &wall),
+            None
+        );
+        // Not far enough
+        assert_eq!(
+            cell(0.0, 5.0).time_of_impact(&Vector(1.0, 0.0), &wall),
+            None
+        );
+    }
+
+    fn assert_normal(normal: Vector, expected: (f64, f64)) {
+        let Vector(x, y) = normal;
+        assert!(
+            (x - expected.0).abs() < EPSILON && (y - expected.1).abs() < EPSILON,
+            "expected {:?}, got {:?}",
+            expected,
+            normal
+        );
+    }
+
+    /// A brick four cells wide, one high.
+    const BRICK: ObjectCoordinates = ObjectCoordinates(10.0, 5.0, 14.0, 6.0);
+
+    #[test]
+    fn normal_of_each_face() {
+        let top = BRICK.face_normal(&cell(11.0, 4.0), &Vector(0.5, 1.0));
+        assert_normal(top, (0.0, -1.0));
+
+        let bottom = BRICK.face_normal(&cell(11.0, 6.0), &Vector(0.5, -1.0));
+        assert_normal(bottom, (0.0, 1.0));
+
+        let left = BRICK.face_normal(&cell(9.0, 5.0), &Vector(1.0, 0.5));
+        assert_normal(left, (-1.0, 0.0));
+
+        let right = BRICK.face_normal(&cell(14.0, 5.0), &Vector(-1.0, -0.5));
+        assert_normal(right, (1.0, 0.0));
+    }
+
+    #[test]
+    fn normal_of_exact_corners() {
+        let diagonal = 1.0 / 2.0_f64.sqrt();
+
+        let top_left = BRICK.face_normal(&cell(9.0, 4.0), &Vector(1.0, 1.0));
+        assert_normal(top_left, (-diagonal, -diagonal));
+
+        let bottom_right = BRICK.face_normal(&cell(14.0, 6.0), &Vector(-1.0, -1.0));
+        assert_normal(bottom_right, (diagonal, diagonal));
+    }
+
+    #[test]
+    fn normal_when_only_moving_towards_one_face() {
+        // On the top left corner but heading up, so it can only have come
+        // in from the left
+        let left = BRICK.face_normal(&cell(9.0, 4.0), &Vector(1.0, -1.0));
+        assert_normal(left, (-1.0, 0.0));
+
+        // Overlapping less across than down, which alone would make it the
+        // left face, but only moving down
+        let top = BRICK.face_normal(&cell(9.8, 4.5), &Vector(0.0, 1.0));
+        assert_normal(top, (0.0, -1.0));
+
+        // Level with the top, moving left from past the right end
+        let right = BRICK.face_normal(&cell(13.5, 4.0), &Vector(-1.0, 0.0));
+        assert_normal(right, (1.0, 0.0));
     }
 }
@@ -1,72 +1,118 @@
-use super::{Collidable, GameDimension, GameObject, GamePhysics, ObjectCoordinates, Vector};
-use crossterm::{
-    cursor,
-    style::{self, style, StyledContent, Stylize},
-    QueueableCommand,
+use super::{
+    Canvas, Collidable, GameDimension, GameObject, GamePhysics, ObjectCoordinates, Vector,
 };
+use crossterm::style::{Color, StyledContent, Stylize};
 
-use std::cmp::*;
-use std::io::{self, stdout, Write};
-
-const MOVE_INCR: u16 = 2;
+use std::io;
 
+#[derive(Clone)]
 pub struct Ball {
-    pub xpos: u16,
-    pub ypos: u16,
+    pub xpos: f64,
+    pub ypos: f64,
     pub radius: u16,
     pub xvelocity: f64,
     pub yvelocity: f64,
+    /// Distance from the left of the paddle while resting on it.
+    held: Option<f64>,
+    color: Color,
     dim: GameDimension,
-    normals: [Vector; 4],
 }
 
 impl Ball {
-    pub fn new(dim: GameDimension) -> Ball {
+    /// A ball with its top left at `x`, `y`, heading straight up at
+    /// `speed` cells per step.
+    pub fn new(x: f64, y: f64, speed: f64, color: Color, dim: GameDimension) -> Ball {
         const BALL_RADIUS: u16 = 0;
         Ball {
-            xpos: dim.1 / 2 - BALL_RADIUS / 2,
-            ypos: dim.0 - 20,
-            xvelocity: 1.0,
-            yvelocity: 1.0,
+            xpos: x,
+            ypos: y,
+            xvelocity: 0.0,
+            yvelocity: -speed,
             radius: BALL_RADIUS,
+            held: None,
+            color,
             dim,
-            normals: [
-                Vector(0.0, -1.0),
-                Vector(0.0, 1.0),
-                Vector(-1.0, 0.0),
-                Vector(1.0, 0.0),
-            ],
         }
     }
 }
 
-impl Collidable for Ball {
-    fn get_coordinates(&self) -> ObjectCoordinates {
-        ObjectCoordinates(
-            self.xpos,
-            self.ypos,
-            self.xpos + 2 * self.radius,
-            self.ypos + 2 * self.radius,
-        )
+impl Ball {
+    /// Cells covered by the ball along either axis.
+    fn size(&self) -> f64 {
+        (2 * self.radius + 1) as f64
+    }
+
+    /// Move along the current velocity for `fraction` of a step.
+    ///
+    /// The ball is free to drop out of the bottom of the playfield.
+    pub fn travel(&mut self, fraction: f64) {
+        self.xpos =
+            (self.xpos + self.xvelocity * fraction).clamp(0.0, self.dim.1 as f64 - self.size());
+        self.ypos = (self.ypos + self.yvelocity * fraction).max(0.0);
     }
 
-    fn has_collision(&self, other: &dyn Collidable) -> bool {
-        let ObjectCoordinates(ax1, ay1, ax2, ay2) = self.get_coordinates();
-        let ObjectCoordinates(bx1, by1, bx2, by2) = other.get_coordinates();
+    /// Keep the direction but travel `speed` cells per step.
+    pub fn set_speed(&mut self, speed: f64) {
+        let Vector(dx, dy) = self.get_velocity().normalized();
+        self.xvelocity = dx * speed;
+        self.yvelocity = dy * speed;
+    }
+
+    /// A copy of the ball heading off `angle` radians clockwise of where
+    /// this one is going.
+    pub fn rotated(&self, angle: f64) -> Ball {
+        let (sin, cos) = angle.sin_cos();
+        Ball {
+            xvelocity: self.xvelocity * cos - self.yvelocity * sin,
+            yvelocity: self.xvelocity * sin + self.yvelocity * cos,
+            held: None,
+            ..self.clone()
+        }
+    }
+
+    /// Come to rest on top of `paddle` where the ball is now, and ride
+    /// along with it until launched.
+    pub fn hold(&mut self, paddle: &dyn Collidable) {
+        let ObjectCoordinates(x1, _, _, _) = paddle.get_coordinates();
+        self.held = Some(self.xpos - x1);
+        self.follow(paddle);
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.held.is_some()
+    }
 
-        let x1 = max(ax1, bx1);
-        let x2 = min(ax2, bx2);
-        let y1 = max(ay1, by1);
-        let y2 = min(ay2, by2);
+    /// Keep up with `paddle` if resting on it.
+    pub fn follow(&mut self, paddle: &dyn Collidable) {
+        let Some(offset) = self.held else {
+            return;
+        };
+        let ObjectCoordinates(x1, y1, x2, _) = paddle.get_coordinates();
+        self.xpos = (x1 + offset).clamp(x1, (x2 - self.size()).max(x1));
+        self.ypos = y1 - self.size();
+    }
 
-        if x1 <= x2 && y1 <= y2 {
-            return true;
+    /// Leave the paddle in `direction` at the current speed, if resting on
+    /// it.
+    pub fn launch(&mut self, direction: Vector) {
+        if self.held.take().is_none() {
+            return;
         }
-        return false;
+        let speed = self.get_velocity().magnitude();
+        let Vector(dx, dy) = direction.normalized();
+        self.xvelocity = dx * speed;
+        self.yvelocity = dy * speed;
     }
+}
 
-    fn get_normal(&self, _other: &dyn Collidable) -> &super::Vector {
-        todo!("do not require ball's normal yet!")
+impl Collidable for Ball {
+    fn get_coordinates(&self) -> ObjectCoordinates {
+        ObjectCoordinates(
+            self.xpos,
+            self.ypos,
+            self.xpos + self.size(),
+            self.ypos + self.size(),
+        )
     }
 
     fn get_velocity(&self) -> super::Vector {
@@ -75,80 +121,82 @@ impl Collidable for Ball {
 }
 
 impl GameObject for Ball {
-    fn fill_object(&self, symbol: StyledContent<&str>) -> io::Result<()> {
-        let mut stdout = stdout();
-
+    fn fill_object(&self, canvas: &mut dyn Canvas, symbol: StyledContent<&str>) -> io::Result<()> {
         const ASPECT_RATIO: f64 = 2.0;
 
-        let xball_from = self.xpos;
-        let xball_to = self.xpos + 2 * self.radius;
+        // Positions are only snapped to the cell grid here
+        let xpos = self.xpos.round() as u16;
+        let ypos = self.ypos.round() as u16;
+
+        let xball_from = xpos;
+        let xball_to = xpos + 2 * self.radius;
 
-        let yball_from = self.ypos;
-        let yball_to = self.ypos + 2 * self.radius;
+        let yball_from = ypos;
+        let yball_to = ypos + 2 * self.radius;
 
         // Rows
         for i in yball_from..=yball_to {
             // Columns
             for j in xball_from..=xball_to {
-                let x: f64 = (j as f64) - (self.xpos + self.radius) as f64;
-                let y: f64 = ((i as f64) - (self.ypos + self.radius) as f64) * ASPECT_RATIO;
+                let x: f64 = (j as f64) - (xpos + self.radius) as f64;
+                let y: f64 = ((i as f64) - (ypos + self.radius) as f64) * ASPECT_RATIO;
 
                 if x * x + y * y <= (self.radius as f64 * self.radius as f64) {
-                    stdout
-                        .queue(cursor::MoveTo(j, i))?
-                        .queue(style::PrintStyledContent(symbol))?;
+                    canvas.put(j, i, symbol)?;
                 }
             }
         }
 
-        stdout.flush()?;
         Ok(())
     }
 
-    fn draw_object(&self) -> io::Result<()> {
-        self.fill_object("●".white())
+    fn draw_object(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
+        self.fill_object(canvas, "●".with(self.color))
     }
 
-    fn clear_object(&self) -> io::Result<()> {
-        self.fill_object(style(" "))
-    }
-
-    fn move_object(&mut self) -> io::Result<()> {
-        self.clear_object()?;
-        let new_xpos =
-            (self.xpos as f64 + self.xvelocity).clamp(0.0, (self.dim.1 - 2 * self.radius) as f64);
-        let new_ypos =
-            (self.ypos as f64 + self.yvelocity).clamp(0.0, (self.dim.0 - 2 * self.radius) as f64);
-        self.xpos = new_xpos as u16;
-        self.ypos = new_ypos as u16;
-        // println!("{} {} {} {}", self.xpos, self.ypos, self.xvelocity, self.yvelocity);
-        Ok(())
+    fn move_object(&mut self) {
+        self.travel(1.0)
     }
 }
 
 impl GamePhysics for Ball {
-    fn update_object(&mut self) -> io::Result<()> {
-        self.move_object();
-        Ok(())
+    fn update_object(&mut self) {
+        self.move_object()
     }
 
-    fn handle_collision(&mut self, other: &dyn Collidable) -> io::Result<()> {
+    fn handle_collision(&mut self, other: &dyn Collidable) {
         if self.has_collision(other) {
             // Retrieve the surface normal from the other object
             let Vector(nx, ny) = other.get_normal(self);
 
             // They are already normal, so not needed
             let normal_magnitude = 1.0; //((nx * nx + ny * ny) as f64).sqrt();
-            let nx = *nx as f64 / normal_magnitude;
-            let ny = *ny as f64 / normal_magnitude;
+            let nx = nx / normal_magnitude;
+            let ny = ny / normal_magnitude;
 
             // Current velocity vector
-            let vx = self.xvelocity as f64;
-            let vy = self.yvelocity as f64;
+            let vx = self.xvelocity;
+            let vy = self.yvelocity;
 
             // Calculate the dot product of the velocity and the normal
             let dot_product = vx * nx + vy * ny;
 
+            // Already moving away from the surface, e.g. still overlapping
+            // it a tick after bouncing
+            if dot_product >= 0.0 {
+                return;
+            }
+
+            // Some surfaces pick the new direction themselves, the ball
+            // keeps its speed
+            if let Some(direction) = other.get_deflection(self) {
+                let speed = self.get_velocity().magnitude();
+                let Vector(dx, dy) = direction.normalized();
+                self.xvelocity = dx * speed;
+                self.yvelocity = dy * speed;
+                return;
+            }
+
             // Calculate the reflected velocity
             let reflected_vx = vx - 2.0 * dot_product * nx;
             let reflected_vy = vy - 2.0 * dot_product * ny;
@@ -158,6 +206,5 @@ impl GamePhysics for Ball {
             self.yvelocity = reflected_vy;
             // println!("{} {}", self.xvelocity, self.yvelocity);
         }
-        Ok(())
     }
 }
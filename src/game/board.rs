@@ -1,139 +1,228 @@
-use crossterm::{
-    cursor,
-    style::{self, style, Stylize},
-    QueueableCommand,
-};
+use crossterm::style::{self, Color, Stylize};
 
+use super::config::Config;
 use super::{
-    Collidable, GameDimension, GameObject, GamePhysics, MoveCommand, ObjectCoordinates, Vector,
+    Canvas, Collidable, GameDimension, GameObject, GamePhysics, MoveCommand, ObjectCoordinates,
+    Vector,
 };
 
-use std::cmp::*;
-use std::io::{self, stdout, Write};
+use std::f64::consts::PI;
+use std::io;
+
+/// Angle from vertical the ball leaves at when it lands on either end of the
+/// paddle.
+const MAX_BOUNCE_ANGLE: f64 = PI / 3.0;
 
-const MOVE_INCR: u16 = 2;
+/// Extra angle added when the paddle is moving at full speed as it is hit.
+const SPIN_ANGLE: f64 = PI / 12.0;
 
 pub struct Board {
-    pub pos: u16,
+    pub pos: f64,
     pub width: u16,
     pub velocity: f64,
+    /// Cells moved per step while a direction is held.
+    speed: f64,
+    color: Color,
     dim: GameDimension,
-    normals: [Vector; 1],
 }
 
 impl Board {
-    pub fn new(dim: GameDimension) -> Board {
-        const BOARD_WIDTH: u16 = 10;
-        let pos = dim.1 / 2 - BOARD_WIDTH / 2 + 20;
-
-        Board {
-            pos,
-            width: BOARD_WIDTH,
+    pub fn new(dim: GameDimension, config: &Config) -> Board {
+        let mut board = Board {
+            pos: 0.0,
+            width: 0,
             velocity: 0.0,
+            speed: config.paddle.speed / config.tick_rate as f64,
+            color: config.colors.paddle,
             dim,
-            normals: [Vector(0.0, -1.0)],
-        }
+        };
+        board.set_width(config.paddle.width);
+        board.pos = ((dim.1 / 2)
+            .saturating_sub(board.width / 2)
+            .saturating_add(20) as f64)
+            .clamp(1.0, board.rightmost());
+        board
     }
-}
 
-impl Collidable for Board {
-    fn get_coordinates(&self) -> ObjectCoordinates {
-        ObjectCoordinates(self.pos, self.dim.0, self.pos + self.width, self.dim.0)
+    /// The furthest right the paddle can go without entering the right
+    /// wall.
+    fn rightmost(&self) -> f64 {
+        self.dim.1.saturating_sub(1 + self.width).max(1) as f64
     }
-    fn has_collision(&self, other: &dyn Collidable) -> bool {
-        let ObjectCoordinates(ax1, ay1, ax2, ay2) = self.get_coordinates();
-        let ObjectCoordinates(bx1, by1, bx2, by2) = other.get_coordinates();
 
-        let x1 = max(ax1, bx1);
-        let x2 = min(ax2, bx2);
-        let y1 = max(ay1, by1);
-        let y2 = min(ay2, by2);
+    /// Grow or shrink to `width` cells around the same centre, staying
+    /// between the side walls.
+    pub fn set_width(&mut self, width: u16) {
+        let width = width.clamp(1, self.dim.1.saturating_sub(2).max(1));
+        self.pos += (self.width as f64 - width as f64) / 2.0;
+        self.width = width;
+        self.pos = self.pos.clamp(1.0, self.rightmost());
+    }
 
-        if x1 <= x2 && y1 <= y2 {
-            return true;
-        }
-        return false;
-    }
-    fn get_normal(&self, other: &dyn Collidable) -> &super::Vector {
-        let ObjectCoordinates(ox1, oy1, _ox2, _oy2) = other.get_coordinates();
-        let ObjectCoordinates(sx1, sy1, _sx2, _sy2) = self.get_coordinates();
-
-        if oy1 <= sy1 {
-            // Collision on top
-            &self.normals[0]
-        } else {
-            println!("{} {}", ox1, sx1);
-            todo!("Cannot hit other surfaces yet!")
-        }
+    /// Direction to send `other` off in from where it is along the paddle:
+    /// straight up from the middle, more and more sideways towards the
+    /// ends, and pushed along if the paddle is moving.
+    pub fn aim(&self, other: &dyn Collidable) -> Vector {
+        let ObjectCoordinates(sx1, _, sx2, _) = self.get_coordinates();
+        let ObjectCoordinates(ox1, _, ox2, _) = other.get_coordinates();
+
+        // -1.0 on the far left of the paddle, 1.0 on the far right
+        let reach = ((sx2 - sx1) + (ox2 - ox1)) / 2.0;
+        let offset = (((ox1 + ox2) - (sx1 + sx2)) / 2.0 / reach).clamp(-1.0, 1.0);
+
+        let angle = (offset * MAX_BOUNCE_ANGLE + self.velocity / self.speed * SPIN_ANGLE)
+            .clamp(-MAX_BOUNCE_ANGLE, MAX_BOUNCE_ANGLE);
+
+        Vector(angle.sin(), -angle.cos())
+    }
+}
+
+impl Collidable for Board {
+    fn get_coordinates(&self) -> ObjectCoordinates {
+        let row = self.dim.0.saturating_sub(1) as f64;
+        ObjectCoordinates(self.pos, row, self.pos + self.width as f64, row + 1.0)
     }
     fn get_velocity(&self) -> super::Vector {
         Vector(self.velocity, 0.0)
     }
+    fn get_deflection(&self, other: &dyn Collidable) -> Option<Vector> {
+        // Hits on the ends of the paddle are plain bounces
+        let Vector(_, ny) = self.get_normal(other);
+        if ny >= 0.0 {
+            return None;
+        }
+        Some(self.aim(other))
+    }
 }
 
 impl GameObject for Board {
-    fn fill_object(&self, symbol: style::StyledContent<&str>) -> io::Result<()> {
-        let mut stdout = stdout();
-
-        let board_l = max(0, self.pos);
-        let board_r = min(self.dim.1, self.pos + self.width);
+    fn fill_object(
+        &self,
+        canvas: &mut dyn Canvas,
+        symbol: style::StyledContent<&str>,
+    ) -> io::Result<()> {
+        let board_l = self.pos.round() as u16;
+        let board_r = (board_l + self.width).min(self.dim.1);
 
         // draw the new board
-        for i in board_l..=board_r {
-            stdout
-                .queue(cursor::MoveTo(i, self.dim.0 - 1))?
-                // seems to write the whole line from y removing previously
-                // drawn characters to the right
-                .queue(style::PrintStyledContent(symbol))?;
-            // .queue(cursor::hide)?;
+        for i in board_l..board_r {
+            canvas.put(i, self.dim.0.saturating_sub(1), symbol)?;
         }
-        stdout.flush();
         Ok(())
     }
 
-    fn draw_object(&self) -> io::Result<()> {
-        self.fill_object("▇".with(style::Color::Green))
+    fn draw_object(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
+        self.fill_object(canvas, "▇".with(self.color))
     }
 
-    fn clear_object(&self) -> io::Result<()> {
-        self.fill_object(style(" "))
-    }
-
-    fn move_object(&mut self) -> io::Result<()> {
-        self.clear_object()?;
-        let new_pos =
-            (self.pos as f64 + self.velocity).clamp(0.0, (self.dim.1 - self.width) as f64);
-        self.pos = new_pos as u16;
-        Ok(())
+    fn move_object(&mut self) {
+        // Stay between the side walls
+        self.pos = (self.pos + self.velocity).clamp(1.0, self.rightmost());
     }
 }
 
 impl MoveCommand for Board {
-    fn move_right(&mut self) -> io::Result<()> {
-        self.velocity = 4.0;
+    fn move_right(&mut self) {
+        self.velocity = self.speed;
         self.move_object()
     }
 
-    fn move_left(&mut self) -> io::Result<()> {
-        self.velocity = -4.0;
+    fn move_left(&mut self) {
+        self.velocity = -self.speed;
         self.move_object()
     }
 
-    fn move_up(&mut self) -> io::Result<()> {
-        Ok(())
-    }
+    fn move_up(&mut self) {}
 
-    fn move_down(&mut self) -> io::Result<()> {
-        Ok(())
-    }
+    fn move_down(&mut self) {}
 }
 
 impl GamePhysics for Board {
-    fn update_object(&mut self) -> io::Result<()> {
-        Ok(())
+    fn update_object(&mut self) {
+        // Only moving on the steps it is told to
+        self.velocity = 0.0;
     }
 
-    fn handle_collision(&mut self, _other: &dyn Collidable) -> io::Result<()> {
-        Ok(())
+    fn handle_collision(&mut self, _other: &dyn Collidable) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ball::Ball;
+    use super::*;
+
+    const DIM: GameDimension = GameDimension(20, 60);
+
+    /// A paddle of the default width across columns 20 to 30.
+    fn board() -> Board {
+        let mut board = Board::new(DIM, &Config::default());
+        board.pos = 20.0;
+        board
+    }
+
+    /// A ball with its top left at `x`, `y`, heading off `angle` radians
+    /// clockwise of straight up.
+    fn ball(x: f64, y: f64, angle: f64) -> Ball {
+        Ball::new(x, y, 1.0, Color::White, DIM).rotated(angle)
+    }
+
+    /// The angle from straight up of `direction`, which should be a unit
+    /// vector heading up.
+    fn angle(direction: Vector) -> f64 {
+        let Vector(dx, dy) = direction;
+        assert!((dx.hypot(dy) - 1.0).abs() < 1e-9 && dy < 0.0);
+        dx.atan2(-dy)
+    }
+
+    fn assert_angle(direction: Vector, expected: f64) {
+        let angle = angle(direction);
+        assert!((angle - expected).abs() < 1e-9, "{} != {}", angle, expected);
+    }
+
+    #[test]
+    fn middle_sends_the_ball_straight_up() {
+        assert_angle(board().aim(&ball(24.5, 18.0, PI)), 0.0);
+    }
+
+    #[test]
+    fn ends_send_the_ball_off_at_the_widest_angle() {
+        let board = board();
+        assert_angle(board.aim(&ball(30.0, 18.0, PI)), MAX_BOUNCE_ANGLE);
+        assert_angle(board.aim(&ball(19.0, 18.0, PI)), -MAX_BOUNCE_ANGLE);
+        // Halfway out
+        assert_angle(board.aim(&ball(27.25, 18.0, PI)), MAX_BOUNCE_ANGLE / 2.0);
+        // Just clipping it counts as the end
+        assert_angle(board.aim(&ball(31.0, 18.0, PI)), MAX_BOUNCE_ANGLE);
+    }
+
+    #[test]
+    fn moving_paddle_adds_spin() {
+        let mut board = board();
+        board.velocity = board.speed;
+        assert_angle(board.aim(&ball(24.5, 18.0, PI)), SPIN_ANGLE);
+        assert_angle(
+            board.aim(&ball(19.0, 18.0, PI)),
+            SPIN_ANGLE - MAX_BOUNCE_ANGLE,
+        );
+        // But never past the widest angle
+        assert_angle(board.aim(&ball(30.0, 18.0, PI)), MAX_BOUNCE_ANGLE);
+
+        board.velocity = -board.speed;
+        assert_angle(board.aim(&ball(24.5, 18.0, PI)), -SPIN_ANGLE);
+        assert_angle(board.aim(&ball(19.0, 18.0, PI)), -MAX_BOUNCE_ANGLE);
+    }
+
+    #[test]
+    fn only_the_top_deflects() {
+        let board = board();
+        let from_above = ball(27.25, 18.5, PI);
+        assert_angle(
+            board.get_deflection(&from_above).unwrap(),
+            MAX_BOUNCE_ANGLE / 2.0,
+        );
+
+        // Into either end from the side
+        assert!(board.get_deflection(&ball(29.5, 19.2, -PI / 2.0)).is_none());
+        assert!(board.get_deflection(&ball(19.5, 19.2, PI / 2.0)).is_none());
     }
 }
@@ -0,0 +1,82 @@
+use super::{Canvas, Collidable, GameObject, GamePhysics, ObjectCoordinates, Vector};
+use crossterm::style::{self, Stylize};
+
+use std::io;
+
+/// Cells a bolt climbs per second.
+const BOLT_SPEED: f64 = 37.5;
+
+/// A laser shot fired up from the paddle. It stops at the first thing it
+/// hits.
+pub struct Bolt {
+    xpos: f64,
+    ypos: f64,
+    /// Cells climbed per step.
+    speed: f64,
+    spent: bool,
+}
+
+impl Bolt {
+    /// A bolt in the cell at `x`, `y`, about to head up, in a game of
+    /// `tick_rate` steps per second.
+    pub fn new(x: f64, y: f64, tick_rate: u32) -> Self {
+        Bolt {
+            xpos: x,
+            ypos: y,
+            speed: BOLT_SPEED / tick_rate as f64,
+            spent: false,
+        }
+    }
+
+    /// Move `fraction` of a step up.
+    pub fn travel(&mut self, fraction: f64) {
+        self.ypos -= self.speed * fraction;
+    }
+
+    pub fn is_spent(&self) -> bool {
+        self.spent
+    }
+}
+
+impl GameObject for Bolt {
+    fn fill_object(
+        &self,
+        canvas: &mut dyn Canvas,
+        symbol: style::StyledContent<&str>,
+    ) -> io::Result<()> {
+        canvas.put(self.xpos.round() as u16, self.ypos.round() as u16, symbol)
+    }
+
+    fn draw_object(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
+        if self.spent {
+            return Ok(());
+        }
+        self.fill_object(canvas, "╿".yellow().bold())
+    }
+
+    fn move_object(&mut self) {
+        self.travel(1.0)
+    }
+}
+
+impl Collidable for Bolt {
+    fn get_velocity(&self) -> Vector {
+        Vector(0.0, -self.speed)
+    }
+
+    fn get_coordinates(&self) -> ObjectCoordinates {
+        ObjectCoordinates(self.xpos, self.ypos, self.xpos + 1.0, self.ypos + 1.0)
+    }
+}
+
+impl GamePhysics for Bolt {
+    fn update_object(&mut self) {
+        self.move_object()
+    }
+
+    fn handle_collision(&mut self, other: &dyn Collidable) {
+        if self.has_collision(other) {
+            self.spent = true;
+        }
+    }
+}
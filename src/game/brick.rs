@@ -1,103 +1,213 @@
-use crossterm::{
-    cursor,
-    style::{self, style, Stylize},
-    QueueableCommand,
-};
+use crossterm::style::{self, Color, Stylize};
 
 use super::{
-    Collidable, GameDimension, GameObject, GamePhysics, MoveCommand, ObjectCoordinates, Vector,
+    Canvas, Collidable, GameDimension, GameObject, GamePhysics, ObjectCoordinates, Vector,
 };
 
-use std::cmp::*;
-use std::io::{self, stdout, Write};
+use std::io;
+
+/// Empty cells an explosion reaches across to the bricks around it.
+const BLAST_RADIUS: f64 = 1.0;
+
+/// Colours of a standard brick by hits left, weakest first.
+const HIT_COLORS: [Color; 4] = [Color::Magenta, Color::Blue, Color::Cyan, Color::Green];
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BrickKind {
+    /// Breaks after this many hits.
+    Standard(u8),
+    /// Never breaks. A level is cleared without it.
+    Steel,
+    /// Breaks on the first hit and takes the bricks around it along.
+    Explosive,
+}
+
+#[derive(Clone)]
 pub struct Brick {
     xpos: u16,
     ypos: u16,
-    normals: [Vector; 1],
+    width: u16,
+    height: u16,
+    kind: BrickKind,
+    points: u32,
+    hits_left: u8,
     destroyed: bool,
 }
 
 impl Brick {
-    pub fn new(x: u16, y: u16) -> Self {
+    /// A `width` x `height` brick with its top left cell at `(x, y)`.
+    pub fn new(x: u16, y: u16, width: u16, height: u16, kind: BrickKind, points: u32) -> Self {
+        let hits_left = match kind {
+            BrickKind::Standard(hits) => hits.max(1),
+            BrickKind::Steel | BrickKind::Explosive => 1,
+        };
         Brick {
             xpos: x,
             ypos: y,
-            normals: [Vector(0.0, 1.0)],
+            width: width.max(1),
+            height: height.max(1),
+            kind,
+            points,
+            hits_left,
             destroyed: false,
         }
     }
-}
 
-impl GameObject for Brick {
-    fn fill_object(&self, symbol: style::StyledContent<&str>) -> io::Result<()> {
-        let mut stdout = stdout();
+    /// Score for destroying this brick, before any combo.
+    pub fn points(&self) -> u32 {
+        self.points
+    }
 
-        stdout
-            .queue(cursor::MoveTo(self.xpos, self.ypos))?
-            .queue(style::PrintStyledContent(symbol))?;
+    pub fn is_destroyed(&self) -> bool {
+        self.destroyed
+    }
 
-        stdout.flush()?;
-        Ok(())
+    /// Whether the brick can be destroyed at all, and so has to be for the
+    /// level to be cleared.
+    pub fn is_breakable(&self) -> bool {
+        self.kind != BrickKind::Steel
     }
 
-    fn draw_object(&self) -> io::Result<()> {
-        if self.destroyed {
-            return Ok(());
+    /// Break the brick outright, however many hits it had left.
+    pub fn destroy(&mut self) {
+        if self.is_breakable() {
+            self.hits_left = 0;
+            self.destroyed = true;
+        }
+    }
+
+    /// Whether `other` is close enough to be caught when this brick
+    /// explodes.
+    pub fn blast_reaches(&self, other: &Brick) -> bool {
+        if self.kind != BrickKind::Explosive {
+            return false;
+        }
+        let a = self.get_coordinates();
+        let b = other.get_coordinates();
+        let gap_x = a.0.max(b.0) - a.2.min(b.2);
+        let gap_y = a.1.max(b.1) - a.3.min(b.3);
+        gap_x <= BLAST_RADIUS && gap_y <= BLAST_RADIUS
+    }
+
+    /// A copy of this brick moved `dx` columns right and `dy` rows down.
+    pub fn translated(&self, dx: u16, dy: u16) -> Brick {
+        Brick {
+            xpos: self.xpos.saturating_add(dx),
+            ypos: self.ypos.saturating_add(dy),
+            ..self.clone()
+        }
+    }
+
+    /// Whether the brick lies inside the walls of a playfield of `dim`.
+    pub fn fits_within(&self, dim: GameDimension) -> bool {
+        let GameDimension(rows, columns) = dim;
+        let right = self.xpos.checked_add(self.width);
+        let bottom = self.ypos.checked_add(self.height);
+        self.xpos >= 1
+            && right.is_some_and(|right| right < columns)
+            && self.ypos >= 1
+            && bottom.is_some_and(|bottom| bottom < rows)
+    }
+
+    /// The symbol for the cell `column`, `row` cells into the brick, so
+    /// that it reads as one block rather than a run of separate cells.
+    fn glyph(&self, column: u16, row: u16) -> &'static str {
+        let left = column == 0;
+        let right = column + 1 == self.width;
+        let top = row == 0;
+        let bottom = row + 1 == self.height;
+
+        if self.width == 1 {
+            return "▆";
+        }
+        if self.height == 1 {
+            return match (left, right) {
+                (true, _) => "╞",
+                (_, true) => "╡",
+                _ => "═",
+            };
+        }
+        match (left, right, top, bottom) {
+            (true, _, true, _) => "┌",
+            (_, true, true, _) => "┐",
+            (true, _, _, true) => "└",
+            (_, true, _, true) => "┘",
+            (_, _, true, _) | (_, _, _, true) => "─",
+            (true, _, _, _) | (_, true, _, _) => "│",
+            _ => " ",
+        }
+    }
+}
+
+impl GameObject for Brick {
+    fn fill_object(
+        &self,
+        canvas: &mut dyn Canvas,
+        symbol: style::StyledContent<&str>,
+    ) -> io::Result<()> {
+        for j in 0..self.height {
+            for i in 0..self.width {
+                canvas.put(self.xpos + i, self.ypos + j, symbol)?;
+            }
         }
-        self.fill_object("▆".magenta())
+        Ok(())
     }
-    fn clear_object(&self) -> io::Result<()> {
+
+    fn draw_object(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
         if self.destroyed {
             return Ok(());
         }
-        self.fill_object(style(" "))
-    }
-    fn move_object(&mut self) -> io::Result<()> {
+        let color = match self.kind {
+            BrickKind::Standard(_) => {
+                HIT_COLORS[(self.hits_left as usize).clamp(1, HIT_COLORS.len()) - 1]
+            }
+            BrickKind::Steel => Color::Grey,
+            BrickKind::Explosive => Color::Red,
+        };
+        for j in 0..self.height {
+            for i in 0..self.width {
+                canvas.put(self.xpos + i, self.ypos + j, self.glyph(i, j).with(color))?;
+            }
+        }
         Ok(())
     }
+    fn move_object(&mut self) {}
 }
 
 impl Collidable for Brick {
     fn get_velocity(&self) -> Vector {
         Vector(0.0, 0.0)
     }
-    fn get_normal(&self, other: &dyn Collidable) -> &Vector {
-        // Only one normal for now
-        &self.normals[0]
-    }
     fn get_coordinates(&self) -> ObjectCoordinates {
-        ObjectCoordinates(self.xpos, self.ypos, self.xpos, self.ypos)
+        let (x, y) = (self.xpos as f64, self.ypos as f64);
+        ObjectCoordinates(x, y, x + self.width as f64, y + self.height as f64)
     }
     fn has_collision(&self, other: &dyn Collidable) -> bool {
         if self.destroyed {
             return false
         }
-        let ObjectCoordinates(ax1, ay1, ax2, ay2) = self.get_coordinates();
-        let ObjectCoordinates(bx1, by1, bx2, by2) = other.get_coordinates();
-
-        let x1 = max(ax1, bx1);
-        let x2 = min(ax2, bx2);
-        let y1 = max(ay1, by1);
-        let y2 = min(ay2, by2);
-
-        if x1 <= x2 && y1 <= y2 {
-            return true;
+        self.get_coordinates().overlaps(&other.get_coordinates())
+    }
+    fn time_of_impact(&self, other: &dyn Collidable, motion: &Vector) -> Option<f64> {
+        if self.destroyed {
+            return None;
         }
-        return false;
+        other
+            .get_coordinates()
+            .time_of_impact(motion, &self.get_coordinates())
     }
 }
 
 impl GamePhysics for Brick {
-    fn update_object(&mut self) -> io::Result<()> {
-        Ok(())
-    }
-    fn handle_collision(&mut self, other: &dyn Collidable) -> io::Result<()> {
-        if self.has_collision(other) {
-            self.clear_object()?;
+    fn update_object(&mut self) {}
+    fn handle_collision(&mut self, other: &dyn Collidable) {
+        if !self.has_collision(other) || !self.is_breakable() {
+            return;
+        }
+        self.hits_left = self.hits_left.saturating_sub(1);
+        if self.hits_left == 0 {
             self.destroyed = true;
         }
-        Ok(())
     }
 }
 
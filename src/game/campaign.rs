@@ -0,0 +1,77 @@
+use super::level::Level;
+
+use std::io;
+use std::path::Path;
+
+/// The levels that ship with the game, in the order they are played.
+const BUILTIN_LEVELS: [&str; 3] = [
+    include_str!("../../levels/01-first-steps.txt"),
+    include_str!("../../levels/02-pyramid.txt"),
+    include_str!("../../levels/03-checkerboard.txt"),
+];
+
+/// An ordered run of levels, played one after the other.
+pub struct Campaign {
+    levels: Vec<Level>,
+    current: usize,
+}
+
+impl Campaign {
+    /// Levels are played in the order given. There has to be at least one.
+    pub fn new(levels: Vec<Level>) -> Self {
+        assert!(!levels.is_empty(), "a campaign needs at least one level");
+        Campaign { levels, current: 0 }
+    }
+
+    /// The campaign that ships with the game.
+    pub fn builtin() -> Self {
+        Campaign::new(
+            BUILTIN_LEVELS
+                .iter()
+                .map(|source| Level::parse(source).expect("built-in levels should be valid"))
+                .collect(),
+        )
+    }
+
+    /// A campaign of level files, played in the order given.
+    pub fn load<P: AsRef<Path>>(paths: &[P]) -> io::Result<Self> {
+        if paths.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a campaign needs at least one level",
+            ));
+        }
+        let levels = paths
+            .iter()
+            .map(|path| Level::load(path.as_ref()))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Campaign::new(levels))
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.levels[self.current]
+    }
+
+    /// 1-based number of the level being played.
+    pub fn number(&self) -> u32 {
+        self.current as u32 + 1
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.current + 1 == self.levels.len()
+    }
+
+    /// Move on to the next level, if there is one.
+    pub fn advance(&mut self) -> bool {
+        if self.is_last() {
+            return false;
+        }
+        self.current += 1;
+        true
+    }
+
+    /// Go back to the first level.
+    pub fn rewind(&mut self) {
+        self.current = 0;
+    }
+}
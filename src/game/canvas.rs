@@ -0,0 +1,354 @@
+use super::Canvas;
+use crossterm::{
+    cursor,
+    style::{self, Attribute, ContentStyle, StyledContent, Stylize},
+    terminal, QueueableCommand,
+};
+use std::io::{self, Write};
+use std::mem;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub symbol: char,
+    pub style: ContentStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            symbol: ' ',
+            style: ContentStyle::default(),
+        }
+    }
+}
+
+/// An in-memory screen, one styled `Cell` per terminal position.
+///
+/// Anything written outside of `width` x `height` is dropped.
+#[derive(Clone)]
+pub struct Frame {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn new(width: u16, height: u16) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells
+            .get(y as usize * self.width as usize + x as usize)
+    }
+
+    /// The symbols of row `y` as a plain string, handy for assertions.
+    pub fn row(&self, y: u16) -> String {
+        (0..self.width)
+            .filter_map(|x| self.get(x, y))
+            .map(|cell| cell.symbol)
+            .collect()
+    }
+
+    /// Blank every cell.
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+    }
+}
+
+impl Canvas for Frame {
+    fn put(&mut self, x: u16, y: u16, symbol: StyledContent<&str>) -> io::Result<()> {
+        if y >= self.height {
+            return Ok(());
+        }
+        for (i, c) in symbol.content().chars().enumerate() {
+            let x = x as usize + i;
+            if x >= self.width as usize {
+                break;
+            }
+            self.cells[y as usize * self.width as usize + x] = Cell {
+                symbol: c,
+                style: *symbol.style(),
+            };
+        }
+        Ok(())
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Part of another canvas, with its own origin.
+pub struct Viewport<'a> {
+    canvas: &'a mut dyn Canvas,
+    x: u16,
+    y: u16,
+}
+
+impl<'a> Viewport<'a> {
+    /// Everything put at `(0, 0)` lands on `(x, y)` of `canvas`.
+    pub fn new(canvas: &'a mut dyn Canvas, x: u16, y: u16) -> Self {
+        Viewport { canvas, x, y }
+    }
+}
+
+impl Canvas for Viewport<'_> {
+    fn put(&mut self, x: u16, y: u16, symbol: StyledContent<&str>) -> io::Result<()> {
+        self.canvas.put(x + self.x, y + self.y, symbol)
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        self.canvas.present()
+    }
+}
+
+/// Draws everything faded, e.g. behind a menu.
+pub struct Dimmed<'a> {
+    canvas: &'a mut dyn Canvas,
+}
+
+impl<'a> Dimmed<'a> {
+    pub fn new(canvas: &'a mut dyn Canvas) -> Self {
+        Dimmed { canvas }
+    }
+}
+
+impl Canvas for Dimmed<'_> {
+    fn put(&mut self, x: u16, y: u16, symbol: StyledContent<&str>) -> io::Result<()> {
+        self.canvas.put(x, y, symbol.dim())
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        self.canvas.present()
+    }
+}
+
+/// Double-buffered crossterm backend.
+///
+/// Objects paint into the back frame; `present` compares it against what is
+/// already on screen and only writes the cells that changed, in one flush.
+pub struct Compositor<W: Write> {
+    out: W,
+    front: Frame,
+    back: Frame,
+}
+
+impl<W: Write> Compositor<W> {
+    /// `front` starts blank, which is what a freshly entered alternate
+    /// screen looks like.
+    pub fn new(out: W, width: u16, height: u16) -> Self {
+        Compositor {
+            out,
+            front: Frame::new(width, height),
+            back: Frame::new(width, height),
+        }
+    }
+
+    /// Start over on a blank screen of a new size, e.g. after the terminal
+    /// was resized and has rearranged or lost what was on it.
+    pub fn resize(&mut self, width: u16, height: u16) -> io::Result<()> {
+        self.out.queue(terminal::Clear(terminal::ClearType::All))?;
+        self.front = Frame::new(width, height);
+        self.back = Frame::new(width, height);
+        Ok(())
+    }
+
+    fn set_style(&mut self, style: &ContentStyle) -> io::Result<()> {
+        self.out
+            .queue(style::ResetColor)?
+            .queue(style::SetAttribute(Attribute::Reset))?
+            .queue(style::SetStyle(*style))?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Canvas for Compositor<W> {
+    fn put(&mut self, x: u16, y: u16, symbol: StyledContent<&str>) -> io::Result<()> {
+        self.back.put(x, y, symbol)
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        let mut style: Option<ContentStyle> = None;
+
+        for y in 0..self.back.height {
+            // Where the terminal cursor would be after the last print on
+            // this row, so that runs of changed cells need a single MoveTo.
+            let mut cursor_x: Option<u16> = None;
+
+            for x in 0..self.back.width {
+                let i = y as usize * self.back.width as usize + x as usize;
+                let cell = &self.back.cells[i];
+                if *cell == self.front.cells[i] {
+                    continue;
+                }
+
+                if cursor_x != Some(x) {
+                    self.out.queue(cursor::MoveTo(x, y))?;
+                }
+                if style != Some(cell.style) {
+                    let next = cell.style;
+                    self.set_style(&next)?;
+                    style = Some(next);
+                }
+                let cell = &self.back.cells[i];
+                self.out.queue(style::Print(cell.symbol))?;
+                cursor_x = Some(x + 1);
+            }
+        }
+
+        if style.is_some() {
+            self.set_style(&ContentStyle::default())?;
+        }
+        self.out.flush()?;
+
+        mem::swap(&mut self.front, &mut self.back);
+        self.back.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ball::Ball;
+    use crate::game::board::Board;
+    use crate::game::brick::{Brick, BrickKind};
+    use crate::game::config::Config;
+    use crate::game::text::{HorizontalAlign, Position, Text, VerticalAlign};
+    use crate::game::wall::{Direction, Wall};
+    use crate::game::{GameDimension, GameObject};
+    use crossterm::style::Color;
+
+    const DIM: GameDimension = GameDimension(6, 20);
+
+    fn frame() -> Frame {
+        Frame::new(DIM.1, DIM.0)
+    }
+
+    #[test]
+    fn ball_fills_a_single_cell() {
+        let mut frame = frame();
+        let ball = Ball::new(3.0, 2.0, 1.0, Color::White, DIM);
+        ball.draw_object(&mut frame).unwrap();
+
+        let cell = frame.get(3, 2).unwrap();
+        assert_eq!(cell.symbol, '●');
+        assert_eq!(cell.style.foreground_color, Some(Color::White));
+        assert_eq!(frame.row(2).trim(), "●");
+    }
+
+    #[test]
+    fn board_is_drawn_on_the_bottom_row() {
+        let mut frame = frame();
+        let mut board = Board::new(DIM, &Config::default());
+        board.pos = 2.0;
+        board.draw_object(&mut frame).unwrap();
+
+        assert_eq!(frame.row(5), "  ▇▇▇▇▇▇▇▇▇▇        ");
+        assert_eq!(frame.row(4).trim(), "");
+    }
+
+    #[test]
+    fn brick_reads_as_one_block() {
+        let mut frame = frame();
+        Brick::new(2, 1, 4, 1, BrickKind::Standard(1), 10)
+            .draw_object(&mut frame)
+            .unwrap();
+        Brick::new(8, 1, 3, 2, BrickKind::Standard(1), 10)
+            .draw_object(&mut frame)
+            .unwrap();
+
+        assert_eq!(frame.row(1), "  ╞══╡  ┌─┐         ");
+        assert_eq!(frame.row(2), "        └─┘         ");
+    }
+
+    #[test]
+    fn walls_line_the_playfield() {
+        let mut frame = frame();
+        Wall::new(DIM, Direction::Left, Color::Green)
+            .draw_object(&mut frame)
+            .unwrap();
+        Wall::new(DIM, Direction::Top, Color::Green)
+            .draw_object(&mut frame)
+            .unwrap();
+
+        assert_eq!(frame.row(0), "─".repeat(20));
+        for y in 1..DIM.0 {
+            assert_eq!(frame.get(0, y).unwrap().symbol, '┃');
+            assert_eq!(frame.get(1, y).unwrap().symbol, ' ');
+        }
+    }
+
+    #[test]
+    fn text_is_boxed_and_aligned() {
+        let mut frame = frame();
+        let position = Position(HorizontalAlign::Right, VerticalAlign::Bottom);
+        Text::new("Hi\nall", position, DIM)
+            .draw_object(&mut frame)
+            .unwrap();
+
+        assert_eq!(frame.row(2), "               ┌───┐");
+        assert_eq!(frame.row(3), "               │Hi │");
+        assert_eq!(frame.row(4), "               │all│");
+        assert_eq!(frame.row(5), "               └───┘");
+    }
+
+    #[test]
+    fn frame_drops_what_is_out_of_bounds() {
+        let mut frame = frame();
+        frame.put(18, 0, "abc".stylize()).unwrap();
+        frame.put(0, 6, "x".stylize()).unwrap();
+
+        assert_eq!(frame.row(0), "                  ab");
+        assert!(frame.get(20, 0).is_none());
+        assert!(frame.get(0, 6).is_none());
+    }
+
+    /// What `present` wrote to the terminal.
+    fn presented(compositor: &mut Compositor<Vec<u8>>) -> String {
+        compositor.present().unwrap();
+        String::from_utf8(mem::take(&mut compositor.out)).unwrap()
+    }
+
+    #[test]
+    fn compositor_skips_unchanged_cells() {
+        let mut compositor = Compositor::new(Vec::new(), DIM.1, DIM.0);
+        compositor.put(2, 1, "ab".red()).unwrap();
+        let first = presented(&mut compositor);
+        assert!(first.contains("\x1b[2;3H"));
+        assert!(first.contains("ab"));
+
+        compositor.put(2, 1, "ab".red()).unwrap();
+        assert_eq!(presented(&mut compositor), "");
+    }
+
+    #[test]
+    fn compositor_writes_only_what_changed() {
+        let mut compositor = Compositor::new(Vec::new(), DIM.1, DIM.0);
+        compositor.put(2, 1, "ab".red()).unwrap();
+        presented(&mut compositor);
+
+        compositor.put(2, 1, "ax".red()).unwrap();
+        let second = presented(&mut compositor);
+        assert!(second.starts_with("\x1b[2;4H"));
+        assert!(second.contains('x'));
+        assert!(!second.contains('a'));
+        assert_eq!(second.matches("\x1b[2;").count(), 1);
+    }
+}
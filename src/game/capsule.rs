@@ -0,0 +1,159 @@
+use super::{
+    Canvas, Collidable, GameDimension, GameObject, GamePhysics, ObjectCoordinates, Vector,
+};
+use crossterm::style::{self, Color, Stylize};
+
+use std::io;
+
+/// Cells a capsule falls per second.
+const FALL_SPEED: f64 = 12.5;
+
+/// Cells taken up by a capsule, e.g. `(W)`.
+const CAPSULE_WIDTH: u16 = 3;
+
+/// What catching a capsule does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUp {
+    /// Wider paddle.
+    Widen,
+    /// Narrower paddle.
+    Shrink,
+    SlowBall,
+    FastBall,
+    ExtraLife,
+    MultiBall,
+    /// The ball sticks to the paddle until launched.
+    Sticky,
+    /// The paddle can fire at bricks.
+    Laser,
+}
+
+impl PowerUp {
+    /// The letter on the capsule, also marking the power-up in the HUD.
+    pub fn symbol(&self) -> char {
+        match self {
+            PowerUp::Widen => 'W',
+            PowerUp::Shrink => 'N',
+            PowerUp::SlowBall => 'S',
+            PowerUp::FastBall => 'F',
+            PowerUp::ExtraLife => '+',
+            PowerUp::MultiBall => 'M',
+            PowerUp::Sticky => 'C',
+            PowerUp::Laser => 'L',
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUp::Widen | PowerUp::SlowBall | PowerUp::ExtraLife => Color::Green,
+            PowerUp::Shrink | PowerUp::FastBall => Color::Red,
+            PowerUp::MultiBall | PowerUp::Sticky => Color::Cyan,
+            PowerUp::Laser => Color::Yellow,
+        }
+    }
+
+    /// Whether the effect wears off after a while rather than happening
+    /// once.
+    pub fn is_timed(&self) -> bool {
+        !matches!(self, PowerUp::ExtraLife | PowerUp::MultiBall)
+    }
+
+    /// The effect this one replaces, as both can't apply at once.
+    pub fn opposite(&self) -> Option<PowerUp> {
+        match self {
+            PowerUp::Widen => Some(PowerUp::Shrink),
+            PowerUp::Shrink => Some(PowerUp::Widen),
+            PowerUp::SlowBall => Some(PowerUp::FastBall),
+            PowerUp::FastBall => Some(PowerUp::SlowBall),
+            _ => None,
+        }
+    }
+}
+
+/// A power-up falling from a destroyed brick, for the paddle to catch.
+pub struct Capsule {
+    xpos: f64,
+    ypos: f64,
+    /// Cells fallen per step.
+    speed: f64,
+    kind: PowerUp,
+    caught: bool,
+    dim: GameDimension,
+}
+
+impl Capsule {
+    /// A capsule centred on `x`, with its top at `y`, in a game of
+    /// `tick_rate` steps per second.
+    pub fn new(x: f64, y: f64, kind: PowerUp, dim: GameDimension, tick_rate: u32) -> Self {
+        Capsule {
+            xpos: x - CAPSULE_WIDTH as f64 / 2.0,
+            ypos: y,
+            speed: FALL_SPEED / tick_rate as f64,
+            kind,
+            caught: false,
+            dim,
+        }
+    }
+
+    pub fn kind(&self) -> PowerUp {
+        self.kind
+    }
+
+    pub fn is_caught(&self) -> bool {
+        self.caught
+    }
+
+    /// Whether it fell past the paddle and is gone.
+    pub fn is_lost(&self) -> bool {
+        self.ypos >= self.dim.0 as f64
+    }
+}
+
+impl GameObject for Capsule {
+    fn fill_object(
+        &self,
+        canvas: &mut dyn Canvas,
+        symbol: style::StyledContent<&str>,
+    ) -> io::Result<()> {
+        canvas.put(self.xpos.round() as u16, self.ypos.round() as u16, symbol)
+    }
+
+    fn draw_object(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
+        if self.caught || self.is_lost() {
+            return Ok(());
+        }
+        let letter = format!("({})", self.kind.symbol());
+        self.fill_object(canvas, letter.as_str().with(self.kind.color()).bold())
+    }
+
+    fn move_object(&mut self) {
+        self.ypos += self.speed;
+    }
+}
+
+impl Collidable for Capsule {
+    fn get_velocity(&self) -> Vector {
+        Vector(0.0, self.speed)
+    }
+
+    fn get_coordinates(&self) -> ObjectCoordinates {
+        ObjectCoordinates(
+            self.xpos,
+            self.ypos,
+            self.xpos + CAPSULE_WIDTH as f64,
+            self.ypos + 1.0,
+        )
+    }
+}
+
+impl GamePhysics for Capsule {
+    fn update_object(&mut self) {
+        self.move_object()
+    }
+
+    fn handle_collision(&mut self, other: &dyn Collidable) {
+        if self.has_collision(other) {
+            self.caught = true;
+        }
+    }
+}
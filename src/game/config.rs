@@ -0,0 +1,348 @@
+use super::keymap::KeyMap;
+use super::ARENA;
+use crossterm::style::Color;
+use serde::{Deserialize, Deserializer};
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Slowest tick rate accepted. Any slower and a capsule could fall past the
+/// paddle between two steps.
+const MIN_TICK_RATE: u32 = 10;
+
+/// Largest tick rate accepted, well past what a terminal can keep up with.
+const MAX_TICK_RATE: u32 = 200;
+
+/// Most lives the player can start with.
+const MAX_LIVES: u32 = 99;
+
+/// Fastest the paddle may move, in cells per second.
+const MAX_PADDLE_SPEED: f64 = 250.0;
+
+/// Most the level's ball speed can be scaled by.
+const MAX_BALL_SPEED: f64 = 3.0;
+
+/// Settings that are up to the player rather than the level.
+///
+/// Read from a TOML file where every key is optional, e.g.
+///
+/// ```toml
+/// tick_rate = 25
+/// lives = 3
+///
+/// [paddle]
+/// width = 10
+/// speed = 100.0
+///
+/// [ball]
+/// speed = 1.0
+///
+/// [colors]
+/// paddle = "green"
+/// ball = "#ffffff"
+///
+/// [keys]
+/// fire = ["Up", "k", "w"]
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Simulation steps per second. Speeds are per second, so this only
+    /// changes how smoothly things move.
+    pub tick_rate: u32,
+    /// Balls the player can lose before the game is over.
+    pub lives: u32,
+    pub paddle: PaddleConfig,
+    pub ball: BallConfig,
+    pub colors: Colors,
+    pub keys: KeyMap,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaddleConfig {
+    /// Cells across before any power-ups.
+    pub width: u16,
+    /// Cells moved per second while a direction is held.
+    pub speed: f64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BallConfig {
+    /// Multiplies the ball speed each level asks for.
+    pub speed: f64,
+}
+
+/// Colors of the pieces, each either a name such as `"dark_cyan"` or
+/// `"#rrggbb"`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    #[serde(deserialize_with = "color")]
+    pub paddle: Color,
+    #[serde(deserialize_with = "color")]
+    pub ball: Color,
+    #[serde(deserialize_with = "color")]
+    pub walls: Color,
+    /// The frame around the screen.
+    #[serde(deserialize_with = "color")]
+    pub border: Color,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            tick_rate: 25,
+            lives: 3,
+            paddle: PaddleConfig::default(),
+            ball: BallConfig::default(),
+            colors: Colors::default(),
+            keys: KeyMap::default(),
+        }
+    }
+}
+
+impl Default for PaddleConfig {
+    fn default() -> Self {
+        PaddleConfig {
+            width: 10,
+            speed: 100.0,
+        }
+    }
+}
+
+impl Default for BallConfig {
+    fn default() -> Self {
+        BallConfig { speed: 1.0 }
+    }
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            paddle: Color::Green,
+            ball: Color::White,
+            walls: Color::Green,
+            border: Color::DarkGrey,
+        }
+    }
+}
+
+impl Config {
+    /// Where the config is read from unless told otherwise:
+    /// `$XDG_CONFIG_HOME/breakit/config.toml`, falling back to `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(dir.join("breakit").join("config.toml"))
+    }
+
+    /// Read and check a config file, naming the file in any error.
+    pub fn load(path: &Path) -> io::Result<Config> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        Config::parse(&source).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// The config at the default path, or the defaults if there is no file
+    /// there.
+    pub fn load_default() -> io::Result<Config> {
+        match Config::default_path() {
+            Some(path) if path.exists() => Config::load(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    pub fn parse(source: &str) -> Result<Config, String> {
+        // The TOML error already points at the offending line
+        let config: Config =
+            toml::from_str(source).map_err(|e| e.to_string().trim_end().to_owned())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject values the game can't be played with.
+    fn validate(&self) -> Result<(), String> {
+        let max_width = ARENA.1 - 2;
+
+        if !(MIN_TICK_RATE..=MAX_TICK_RATE).contains(&self.tick_rate) {
+            return Err(format!(
+                "tick_rate must be between {} and {}, got {}",
+                MIN_TICK_RATE, MAX_TICK_RATE, self.tick_rate
+            ));
+        }
+        if !(1..=MAX_LIVES).contains(&self.lives) {
+            return Err(format!(
+                "lives must be between 1 and {}, got {}",
+                MAX_LIVES, self.lives
+            ));
+        }
+        if !(1..=max_width).contains(&self.paddle.width) {
+            return Err(format!(
+                "paddle.width must be between 1 and {}, got {}",
+                max_width, self.paddle.width
+            ));
+        }
+        if !(self.paddle.speed > 0.0 && self.paddle.speed <= MAX_PADDLE_SPEED) {
+            return Err(format!(
+                "paddle.speed must be above 0 and at most {}, got {}",
+                MAX_PADDLE_SPEED, self.paddle.speed
+            ));
+        }
+        if !(self.ball.speed > 0.0 && self.ball.speed <= MAX_BALL_SPEED) {
+            return Err(format!(
+                "ball.speed must be above 0 and at most {}, got {}",
+                MAX_BALL_SPEED, self.ball.speed
+            ));
+        }
+        self.keys.validate()
+    }
+}
+
+/// A color from its name or `#rrggbb`.
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    parse_color(&name).ok_or_else(|| {
+        serde::de::Error::custom(format!(
+            "unknown color `{}`, expected a name such as `dark_cyan` or `#rrggbb`",
+            name
+        ))
+    })
+}
+
+fn parse_color(name: &str) -> Option<Color> {
+    let Some(hex) = name.strip_prefix('#') else {
+        return Color::try_from(name).ok();
+    };
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::Rgb {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_fields_are_rejected_where_they_are() {
+        let cases = [
+            ("speed = 1", "line 1, column 1", "unknown field `speed`"),
+            ("[sound]\n", "line 1, column 2", "unknown field `sound`"),
+            (
+                "[paddle]\nheight = 2",
+                "line 2, column 1",
+                "unknown field `height`",
+            ),
+            (
+                "[ball]\nspin = 1",
+                "line 2, column 1",
+                "unknown field `spin`",
+            ),
+            (
+                "[colors]\nbrick = \"red\"",
+                "line 2, column 1",
+                "unknown field `brick`",
+            ),
+            (
+                "[keys]\njump = [\"j\"]",
+                "line 2, column 1",
+                "unknown field `jump`",
+            ),
+        ];
+        for (source, place, message) in cases {
+            let error = Config::parse(source).unwrap_err();
+            assert!(
+                error.starts_with(&format!("TOML parse error at {}", place)),
+                "{}",
+                error
+            );
+            assert!(
+                error.lines().last().unwrap().starts_with(message),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn values_are_checked() {
+        let cases = [
+            (
+                "tick_rate = 5",
+                "tick_rate must be between 10 and 200, got 5",
+            ),
+            (
+                "tick_rate = 201",
+                "tick_rate must be between 10 and 200, got 201",
+            ),
+            ("lives = 0", "lives must be between 1 and 99, got 0"),
+            (
+                "[paddle]\nwidth = 69",
+                "paddle.width must be between 1 and 68, got 69",
+            ),
+            (
+                "[paddle]\nspeed = 0.0",
+                "paddle.speed must be above 0 and at most 250, got 0",
+            ),
+            (
+                "[ball]\nspeed = 3.5",
+                "ball.speed must be above 0 and at most 3, got 3.5",
+            ),
+            ("[keys]\nfire = []", "keys.fire needs at least one key"),
+            (
+                "[keys]\npause = [\"q\"]",
+                "key `q` is bound to both keys.pause and keys.quit",
+            ),
+        ];
+        assert!(Config::parse("").is_ok());
+        for (source, error) in cases {
+            assert_eq!(Config::parse(source).unwrap_err(), error);
+        }
+    }
+
+    #[test]
+    fn colors_are_parsed_by_name_or_hex() {
+        let cases = [
+            ("dark_cyan", Some(Color::DarkCyan)),
+            (
+                "#ff8000",
+                Some(Color::Rgb {
+                    r: 255,
+                    g: 128,
+                    b: 0,
+                }),
+            ),
+            (
+                "#FF8000",
+                Some(Color::Rgb {
+                    r: 255,
+                    g: 128,
+                    b: 0,
+                }),
+            ),
+            ("#ff80", None),
+            ("#ff80zz", None),
+            ("#ff80é", None),
+            ("mauve", None),
+        ];
+        for (name, color) in cases {
+            assert_eq!(parse_color(name), color, "parsing `{}`", name);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crossterm::event::{
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::{cursor, event, terminal, QueueableCommand};
+use std::io::{self, stdout, Write};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+/// Whether the terminal was asked to report key releases, so that it can be
+/// told to stop again.
+static ENHANCED: AtomicBool = AtomicBool::new(false);
+
+/// Puts the terminal into the state the game needs and back again.
+///
+/// The terminal is restored when the guard is dropped, whether the game
+/// returns normally or with an error, and by a panic hook before the panic
+/// message is printed, so that the message ends up on the normal screen.
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    /// Switch to the alternate screen with raw input, focus events and a
+    /// hidden cursor, and key releases reported where the terminal can.
+    pub fn enter() -> io::Result<Self> {
+        install_panic_hook();
+
+        // From here on, dropping the guard undoes whatever got done
+        let guard = TerminalGuard { _private: () };
+
+        let mut stdout = stdout();
+        stdout
+            .queue(terminal::EnterAlternateScreen)?
+            .queue(event::EnableFocusChange)?
+            .queue(cursor::Hide)?;
+        stdout.flush()?;
+        terminal::enable_raw_mode()?;
+
+        // Terminals that don't answer the query can't do it either
+        if terminal::supports_keyboard_enhancement().unwrap_or(false) {
+            ENHANCED.store(true, Ordering::SeqCst);
+            stdout.queue(PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES,
+            ))?;
+            stdout.flush()?;
+        }
+
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // Nothing sensible to do if this fails too
+        let _ = restore();
+    }
+}
+
+/// Undo everything `TerminalGuard::enter` did. Safe to call more than once.
+fn restore() -> io::Result<()> {
+    let raw_mode = terminal::disable_raw_mode();
+
+    let mut stdout = stdout();
+    if ENHANCED.swap(false, Ordering::SeqCst) {
+        stdout.queue(PopKeyboardEnhancementFlags)?;
+    }
+    stdout
+        .queue(cursor::Show)?
+        .queue(event::DisableFocusChange)?
+        .queue(terminal::LeaveAlternateScreen)?;
+    stdout.flush()?;
+
+    raw_mode
+}
+
+/// Restore the terminal before the default hook prints a panic, however
+/// many guards get created.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            default_hook(info);
+        }));
+    });
+}
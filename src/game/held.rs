@@ -0,0 +1,160 @@
+use super::keymap::Action;
+use crossterm::event::KeyCode;
+
+use std::time::{Duration, Instant};
+
+/// How long a direction counts as held after its key was last pressed or
+/// repeated, for keys that don't report releases.
+const HOLD_TIME: Duration = Duration::from_millis(120);
+
+/// Which way the player is holding the paddle, whatever the key repeat
+/// settings of the machine.
+///
+/// A key that has reported a release before is held from press to release.
+/// Any other key, which is every key on terminals that don't report releases
+/// and keys that type text on some that do, is held for `HOLD_TIME` after
+/// every press or repeat.
+#[derive(Default)]
+pub struct HeldKeys {
+    /// The key left and right were last pressed with, and when, while held.
+    left: Option<(KeyCode, Instant)>,
+    right: Option<(KeyCode, Instant)>,
+    /// Keys that have reported a release.
+    releasing: Vec<KeyCode>,
+}
+
+impl HeldKeys {
+    pub fn new() -> Self {
+        HeldKeys::default()
+    }
+
+    fn slot(&mut self, action: Action) -> Option<&mut Option<(KeyCode, Instant)>> {
+        match action {
+            Action::MoveLeft => Some(&mut self.left),
+            Action::MoveRight => Some(&mut self.right),
+            _ => None,
+        }
+    }
+
+    /// `key`, bound to `action`, went down or repeated at `now`.
+    pub fn press(&mut self, action: Action, key: KeyCode, now: Instant) {
+        if let Some(slot) = self.slot(action) {
+            *slot = Some((key, now));
+        }
+    }
+
+    /// `key`, bound to `action`, was let go.
+    pub fn release(&mut self, action: Action, key: KeyCode) {
+        if !self.releasing.contains(&key) {
+            self.releasing.push(key);
+        }
+        if let Some(slot) = self.slot(action) {
+            if matches!(slot, Some((pressed, _)) if *pressed == key) {
+                *slot = None;
+            }
+        }
+    }
+
+    /// Forget every key, e.g. when releases may have been missed.
+    pub fn release_all(&mut self) {
+        self.left = None;
+        self.right = None;
+    }
+
+    fn is_held(&self, (key, pressed): (KeyCode, Instant), now: Instant) -> bool {
+        self.releasing.contains(&key) || now.duration_since(pressed) < HOLD_TIME
+    }
+
+    /// The direction held at `now`, if any. When both are, the one pressed
+    /// last wins.
+    pub fn direction(&self, now: Instant) -> Option<Action> {
+        let left = self.left.filter(|press| self.is_held(*press, now));
+        let right = self.right.filter(|press| self.is_held(*press, now));
+        match (left, right) {
+            (Some((_, l)), Some((_, r))) if r > l => Some(Action::MoveRight),
+            (Some(_), _) => Some(Action::MoveLeft),
+            (None, Some(_)) => Some(Action::MoveRight),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: KeyCode = KeyCode::Left;
+    const RIGHT: KeyCode = KeyCode::Right;
+
+    #[test]
+    fn presses_wear_off_without_releases() {
+        let start = Instant::now();
+        let mut held = HeldKeys::new();
+        held.press(Action::MoveLeft, LEFT, start);
+
+        assert_eq!(held.direction(start), Some(Action::MoveLeft));
+        assert_eq!(
+            held.direction(start + HOLD_TIME / 2),
+            Some(Action::MoveLeft)
+        );
+        assert_eq!(held.direction(start + HOLD_TIME), None);
+    }
+
+    #[test]
+    fn keys_are_held_until_released() {
+        let start = Instant::now();
+        let mut held = HeldKeys::new();
+        held.press(Action::MoveRight, RIGHT, start);
+        held.release(Action::MoveRight, RIGHT);
+        assert_eq!(held.direction(start), None);
+
+        held.press(Action::MoveRight, RIGHT, start);
+        assert_eq!(
+            held.direction(start + HOLD_TIME * 10),
+            Some(Action::MoveRight)
+        );
+        held.release(Action::MoveRight, RIGHT);
+        assert_eq!(held.direction(start + HOLD_TIME * 10), None);
+    }
+
+    #[test]
+    fn keys_without_releases_wear_off_next_to_ones_with() {
+        // Kitty style terminals report releases of the arrows, but not of
+        // keys that type text
+        let start = Instant::now();
+        let mut held = HeldKeys::new();
+        held.press(Action::MoveLeft, LEFT, start);
+        held.release(Action::MoveLeft, LEFT);
+
+        held.press(Action::MoveLeft, KeyCode::Char('h'), start);
+        assert_eq!(
+            held.direction(start + HOLD_TIME / 2),
+            Some(Action::MoveLeft)
+        );
+        assert_eq!(held.direction(start + HOLD_TIME), None);
+
+        held.press(Action::MoveLeft, LEFT, start);
+        assert_eq!(held.direction(start + HOLD_TIME), Some(Action::MoveLeft));
+        // Releasing another key bound to the same direction doesn't count
+        held.release(Action::MoveLeft, KeyCode::Char('a'));
+        assert_eq!(held.direction(start + HOLD_TIME), Some(Action::MoveLeft));
+    }
+
+    #[test]
+    fn last_pressed_direction_wins() {
+        let start = Instant::now();
+        let mut held = HeldKeys::new();
+        held.release(Action::MoveLeft, LEFT);
+        held.release(Action::MoveRight, RIGHT);
+        held.press(Action::MoveLeft, LEFT, start);
+        held.press(Action::MoveRight, RIGHT, start + Duration::from_millis(10));
+        assert_eq!(held.direction(start + HOLD_TIME), Some(Action::MoveRight));
+
+        held.release(Action::MoveRight, RIGHT);
+        assert_eq!(held.direction(start + HOLD_TIME), Some(Action::MoveLeft));
+
+        held.press(Action::Fire, KeyCode::Up, start);
+        held.release_all();
+        assert_eq!(held.direction(start), None);
+    }
+}
@@ -0,0 +1,135 @@
+use super::capsule::PowerUp;
+use super::simulation::Simulation;
+use super::text::{HorizontalAlign, Position, Text, VerticalAlign};
+use super::{Canvas, GameDimension, GameObject};
+
+use std::io;
+
+/// Rows taken up by the HUD above the playfield.
+pub const HUD_HEIGHT: u16 = 3;
+
+/// Score, level, power-ups and lives along the top of the screen.
+pub struct Hud {
+    score: Text,
+    level: Text,
+    lives: Text,
+    dim: GameDimension,
+}
+
+impl Hud {
+    pub fn new(dim: GameDimension) -> Self {
+        Hud {
+            score: Text::new("", Position(HorizontalAlign::Left, VerticalAlign::Top), dim),
+            level: Text::new(
+                "",
+                Position(HorizontalAlign::Centre, VerticalAlign::Top),
+                dim,
+            ),
+            lives: Text::new(
+                "",
+                Position(HorizontalAlign::Right, VerticalAlign::Top),
+                dim,
+            ),
+            dim,
+        }
+    }
+
+    /// Refresh from `sim`, showing power-up timers in seconds at
+    /// `tick_rate` steps per second.
+    pub fn update(&mut self, sim: &Simulation, level: u32, tick_rate: u32) {
+        let multiplier = sim.multiplier();
+        let score = if multiplier > 1 {
+            format!("Score {} x{}", sim.score(), multiplier)
+        } else {
+            format!("Score {}", sim.score())
+        };
+        let lives = format!("Lives {}", sim.lives());
+
+        // The level box is centred, so it has to keep clear of the wider of
+        // the boxes either side
+        let side = box_width(&score).max(box_width(&lives));
+        let room = self.dim.1.saturating_sub(2 * side);
+        let centre = centre(level, sim.effects(), tick_rate, room);
+
+        self.score.set_content(&score);
+        self.level.set_content(&centre);
+        self.lives.set_content(&lives);
+    }
+
+    pub fn draw(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
+        self.score.draw_object(canvas)?;
+        self.level.draw_object(canvas)?;
+        self.lives.draw_object(canvas)
+    }
+}
+
+/// Columns taken up by a box around the single line `text`.
+fn box_width(text: &str) -> u16 {
+    text.chars().count() as u16 + 2
+}
+
+/// The level number and how many seconds each power-up in `effects` has
+/// left, e.g. `Level 2 │ W15s L3s`, leaving out timers that would make the
+/// box wider than `room`.
+fn centre(level: u32, effects: &[(PowerUp, u32)], tick_rate: u32, room: u16) -> String {
+    let mut centre = format!("Level {}", level);
+    for (i, (kind, steps)) in effects.iter().enumerate() {
+        let separator = if i == 0 { " │ " } else { " " };
+        let timer = format!(
+            "{}{}{}s",
+            separator,
+            kind.symbol(),
+            steps.div_ceil(tick_rate)
+        );
+        if box_width(&centre) + timer.chars().count() as u16 > room {
+            break;
+        }
+        centre.push_str(&timer);
+    }
+    centre
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_are_short() {
+        let effects = [
+            (PowerUp::Widen, 375),
+            (PowerUp::FastBall, 250),
+            (PowerUp::Sticky, 26),
+            (PowerUp::Laser, 1),
+        ];
+        assert_eq!(centre(3, &[], 25, 70), "Level 3");
+        assert_eq!(centre(3, &effects, 25, 70), "Level 3 │ W15s F10s C2s L1s");
+    }
+
+    #[test]
+    fn timers_that_do_not_fit_are_left_out() {
+        let effects = [(PowerUp::Widen, 375), (PowerUp::Laser, 375)];
+        // "Level 3 │ W15s" is 14 columns, 16 with the box
+        assert_eq!(centre(3, &effects, 25, 16), "Level 3 │ W15s");
+        assert_eq!(centre(3, &effects, 25, 15), "Level 3");
+    }
+
+    #[test]
+    fn level_box_stays_between_the_others() {
+        // The widest the sides get, with every timed power-up there can be
+        // at once
+        let side = box_width(&format!("Score {} x{}", 9_999_999, 8));
+        let effects = [
+            (PowerUp::Shrink, 375),
+            (PowerUp::SlowBall, 375),
+            (PowerUp::Sticky, 375),
+            (PowerUp::Laser, 375),
+        ];
+        let dim = super::super::SCREEN;
+        let centre = centre(99, &effects, 25, dim.1 - 2 * side);
+        assert_eq!(centre, "Level 99 │ N15s S15s C15s L15s");
+
+        let width = box_width(&centre);
+        let x = dim.1 / 2 - width / 2;
+        assert!(x >= side && x + width <= dim.1 - side);
+    }
+}
@@ -0,0 +1,284 @@
+use crossterm::event::KeyCode;
+use serde::{Deserialize, Deserializer};
+
+/// Something the player can ask for, whatever key it is bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    /// Send off a ball resting on the paddle, or start a game.
+    Launch,
+    /// Shoot from the paddle, if it has a laser.
+    Fire,
+    Pause,
+    /// Show the controls.
+    Help,
+    Quit,
+}
+
+impl Action {
+    /// In the order they are listed on the help screen.
+    pub const ALL: [Action; 7] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Launch,
+        Action::Fire,
+        Action::Pause,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// Key of the action in the config file.
+    fn name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Launch => "launch",
+            Action::Fire => "fire",
+            Action::Pause => "pause",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// What the action is called on the help screen.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Launch => "Launch",
+            Action::Fire => "Fire",
+            Action::Pause => "Pause",
+            Action::Help => "Controls",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// The keys bound to each action. Arrows, vim keys and WASD all work out
+/// of the box.
+///
+/// In the config file each action takes a list of keys, each either a
+/// single character or a name such as `Left`, `Space`, `Esc` or `F1`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyMap {
+    #[serde(deserialize_with = "keys")]
+    move_left: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    move_right: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    launch: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    fire: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    pause: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    help: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    quit: Vec<KeyCode>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap {
+            move_left: vec![KeyCode::Left, KeyCode::Char('h'), KeyCode::Char('a')],
+            move_right: vec![KeyCode::Right, KeyCode::Char('l'), KeyCode::Char('d')],
+            launch: vec![KeyCode::Char(' ')],
+            fire: vec![KeyCode::Up, KeyCode::Char('k'), KeyCode::Char('w')],
+            pause: vec![KeyCode::Char('p')],
+            help: vec![KeyCode::Char('?'), KeyCode::F(1)],
+            quit: vec![KeyCode::Esc, KeyCode::Char('q')],
+        }
+    }
+}
+
+impl KeyMap {
+    /// Keys bound to `action`, the one shown in hints first.
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        match action {
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::Launch => &self.launch,
+            Action::Fire => &self.fire,
+            Action::Pause => &self.pause,
+            Action::Help => &self.help,
+            Action::Quit => &self.quit,
+        }
+    }
+
+    /// What pressing `key` asks for, if anything.
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| self.keys(*action).contains(&key))
+    }
+
+    /// The first key bound to `action`, as shown in hints.
+    pub fn hint(&self, action: Action) -> String {
+        self.keys(action)
+            .first()
+            .map(|key| key_name(*key))
+            .unwrap_or_default()
+    }
+
+    /// Every action needs a key, and no key can do two things.
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, action) in Action::ALL.iter().enumerate() {
+            if self.keys(*action).is_empty() {
+                return Err(format!("keys.{} needs at least one key", action.name()));
+            }
+            for key in self.keys(*action) {
+                if let Some(other) = Action::ALL[i + 1..]
+                    .iter()
+                    .find(|other| self.keys(**other).contains(key))
+                {
+                    return Err(format!(
+                        "key `{}` is bound to both keys.{} and keys.{}",
+                        key_name(*key),
+                        action.name(),
+                        other.name()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How `key` is written on screen.
+pub fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
+
+/// A list of keys from their names in the config file.
+fn keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<KeyCode>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|name| {
+            parse_key(name).ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "unknown key `{}`, expected a single character or a name such as `Left`, `Space` or `F1`",
+                    name
+                ))
+            })
+        })
+        .collect()
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    let key = match name.to_lowercase().as_str() {
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        lower => {
+            let n = lower.strip_prefix('f')?.parse::<u8>().ok()?;
+            if !(1..=12).contains(&n) {
+                return None;
+            }
+            KeyCode::F(n)
+        }
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_parsed_by_character_or_name() {
+        let cases = [
+            ("a", Some(KeyCode::Char('a'))),
+            ("?", Some(KeyCode::Char('?'))),
+            (" ", Some(KeyCode::Char(' '))),
+            ("Left", Some(KeyCode::Left)),
+            ("RIGHT", Some(KeyCode::Right)),
+            ("up", Some(KeyCode::Up)),
+            ("Down", Some(KeyCode::Down)),
+            ("Space", Some(KeyCode::Char(' '))),
+            ("Enter", Some(KeyCode::Enter)),
+            ("Return", Some(KeyCode::Enter)),
+            ("Esc", Some(KeyCode::Esc)),
+            ("Escape", Some(KeyCode::Esc)),
+            ("Tab", Some(KeyCode::Tab)),
+            ("Backspace", Some(KeyCode::Backspace)),
+            ("F1", Some(KeyCode::F(1))),
+            ("f12", Some(KeyCode::F(12))),
+            ("F0", None),
+            ("F13", None),
+            ("F", Some(KeyCode::Char('F'))),
+            ("Fx", None),
+            ("", None),
+            ("ab", None),
+            ("Home", None),
+        ];
+        for (name, key) in cases {
+            assert_eq!(parse_key(name), key, "parsing `{}`", name);
+        }
+    }
+
+    #[test]
+    fn keys_cannot_be_bound_twice() {
+        let default = KeyMap::default();
+        let cases = [
+            (
+                KeyMap {
+                    fire: vec![KeyCode::Char('a')],
+                    ..default.clone()
+                },
+                "key `a` is bound to both keys.move_left and keys.fire",
+            ),
+            (
+                KeyMap {
+                    quit: vec![KeyCode::Char(' ')],
+                    ..default.clone()
+                },
+                "key `Space` is bound to both keys.launch and keys.quit",
+            ),
+            (
+                KeyMap {
+                    move_right: vec![KeyCode::Right, KeyCode::Right],
+                    pause: vec![KeyCode::Right],
+                    ..default.clone()
+                },
+                "key `→` is bound to both keys.move_right and keys.pause",
+            ),
+            (
+                KeyMap {
+                    help: vec![],
+                    ..default.clone()
+                },
+                "keys.help needs at least one key",
+            ),
+        ];
+        assert_eq!(default.validate(), Ok(()));
+        for (keys, error) in cases {
+            assert_eq!(keys.validate(), Err(error.to_string()));
+        }
+    }
+}
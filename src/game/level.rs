@@ -0,0 +1,417 @@
+use super::brick::{Brick, BrickKind};
+use super::{GameDimension, ARENA};
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Rows left empty between the top wall and the first row of bricks.
+const TOP_GAP: u16 = 2;
+
+/// Score for each hit a standard brick takes to break, and for an
+/// explosive one, unless the level says otherwise.
+const DEFAULT_STANDARD_POINTS: u32 = 10;
+const DEFAULT_EXPLOSIVE_POINTS: u32 = 10;
+
+const DEFAULT_BALL_SPEED: f64 = 25.0;
+
+/// Cells taken up by each brick unless the level says otherwise.
+const DEFAULT_BRICK_SIZE: (u16, u16) = (1, 1);
+
+/// Marks the end of the header.
+const SEPARATOR: &str = "---";
+
+/// A brick layout and the settings to play it with.
+///
+/// Levels are plain text: a header of `key: value` lines, a `---` line,
+/// then a grid with one character per brick. See `levels/` for examples.
+pub struct Level {
+    pub name: String,
+    /// Cells the ball travels per second.
+    pub ball_speed: f64,
+    /// How long a good run takes to clear the level.
+    pub par_time: Option<Duration>,
+    /// Bricks relative to the top left corner of the grid.
+    bricks: Vec<Brick>,
+    /// Columns taken up by the bricks.
+    width: u16,
+}
+
+/// Why a level could not be parsed, and where.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelError {
+    /// 1-based line of the level text.
+    pub line: usize,
+    /// 1-based column of the level text.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl LevelError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        LevelError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl Level {
+    /// Read and parse a level file, naming the file in any error.
+    pub fn load(path: &Path) -> io::Result<Level> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        Level::parse(&source).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })
+    }
+
+    pub fn parse(source: &str) -> Result<Level, LevelError> {
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        let mut name = None;
+        let mut ball_speed = DEFAULT_BALL_SPEED;
+        let mut par_time = None;
+        let mut brick_size = DEFAULT_BRICK_SIZE;
+        let mut points = BrickPoints {
+            standard: DEFAULT_STANDARD_POINTS,
+            explosive: DEFAULT_EXPLOSIVE_POINTS,
+        };
+
+        // Header
+        let mut separator_line = None;
+        for (number, line) in lines.by_ref() {
+            let trimmed = line.trim();
+            if trimmed == SEPARATOR {
+                separator_line = Some(number);
+                break;
+            }
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let indent = line.len() - line.trim_start().len();
+            let Some((key, value)) = trimmed.split_once(':') else {
+                return Err(LevelError::new(
+                    number,
+                    indent + 1,
+                    format!("expected `key: value` or `{}`", SEPARATOR),
+                ));
+            };
+            let key = key.trim();
+            // Where the value starts, for pointing at bad values
+            let column = line.find(':').unwrap_or(0) + 2 + (value.len() - value.trim_start().len());
+            let value = value.trim();
+
+            match key {
+                "name" if value.is_empty() => {
+                    return Err(LevelError::new(number, column, "name cannot be empty"))
+                }
+                "name" => name = Some(value.to_string()),
+                "ball_speed" => {
+                    ball_speed = match value.parse::<f64>() {
+                        Ok(speed) if speed > 0.0 && speed.is_finite() => speed,
+                        _ => {
+                            return Err(LevelError::new(
+                                number,
+                                column,
+                                format!("ball_speed must be a positive number, found `{}`", value),
+                            ))
+                        }
+                    }
+                }
+                "par_time" => {
+                    par_time = match value.parse::<u64>() {
+                        Ok(seconds) => Some(Duration::from_secs(seconds)),
+                        Err(_) => {
+                            return Err(LevelError::new(
+                                number,
+                                column,
+                                format!("par_time must be whole seconds, found `{}`", value),
+                            ))
+                        }
+                    }
+                }
+                "standard_points" | "explosive_points" => {
+                    let Ok(value) = value.parse::<u32>() else {
+                        return Err(LevelError::new(
+                            number,
+                            column,
+                            format!("{} must be a whole number, found `{}`", key, value),
+                        ));
+                    };
+                    if key == "standard_points" {
+                        points.standard = value;
+                    } else {
+                        points.explosive = value;
+                    }
+                }
+                "brick_size" => {
+                    brick_size = match parse_size(value) {
+                        Some(size) => size,
+                        None => {
+                            return Err(LevelError::new(
+                                number,
+                                column,
+                                format!("brick_size must look like `4x1`, found `{}`", value),
+                            ))
+                        }
+                    }
+                }
+                _ => {
+                    return Err(LevelError::new(
+                        number,
+                        indent + 1,
+                        format!("unknown setting `{}`", key),
+                    ))
+                }
+            }
+        }
+
+        let Some(separator_line) = separator_line else {
+            let last = source.lines().count().max(1);
+            return Err(LevelError::new(
+                last,
+                1,
+                format!(
+                    "missing `{}` line between the header and the bricks",
+                    SEPARATOR
+                ),
+            ));
+        };
+        let Some(name) = name else {
+            return Err(LevelError::new(
+                separator_line,
+                1,
+                "missing `name` in the header",
+            ));
+        };
+
+        // Grid, one character per brick, which has to fit inside the walls
+        // of the arena
+        let (brick_width, brick_height) = brick_size;
+        let max_width = ARENA.1 as usize - 2;
+        let max_height = ARENA.0 as usize - 2 - TOP_GAP as usize;
+        let mut bricks = Vec::new();
+        let mut width = 0;
+        for (row, (number, line)) in lines.enumerate() {
+            let cells = line.trim_end().chars().count();
+            if let Some(needed) = cells
+                .checked_mul(brick_width as usize)
+                .filter(|needed| *needed > max_width)
+            {
+                return Err(LevelError::new(
+                    number,
+                    max_width / brick_width as usize + 1,
+                    format!(
+                        "the bricks must fit in {} columns, this row takes {}",
+                        max_width, needed
+                    ),
+                ));
+            }
+            width = width.max(cells as u16 * brick_width);
+
+            let bottom = (row + 1).saturating_mul(brick_height as usize);
+            let mut brick_at = |column: usize, kind| {
+                if bottom > max_height {
+                    return Err(LevelError::new(
+                        number,
+                        column + 1,
+                        format!(
+                            "the bricks must fit in {} rows, this one reaches row {}",
+                            max_height, bottom
+                        ),
+                    ));
+                }
+                let x = column as u16 * brick_width;
+                let y = row as u16 * brick_height;
+                bricks.push(Brick::new(
+                    x,
+                    y,
+                    brick_width,
+                    brick_height,
+                    kind,
+                    points.of(kind),
+                ));
+                Ok(())
+            };
+            for (column, symbol) in line.chars().enumerate() {
+                match symbol {
+                    '.' | ' ' => (),
+                    '#' => brick_at(column, BrickKind::Standard(1))?,
+                    '2'..='4' => {
+                        let hits = symbol.to_digit(10).unwrap_or(1) as u8;
+                        brick_at(column, BrickKind::Standard(hits))?
+                    }
+                    '=' => brick_at(column, BrickKind::Steel)?,
+                    '*' => brick_at(column, BrickKind::Explosive)?,
+                    _ => {
+                        return Err(LevelError::new(
+                            number,
+                            column + 1,
+                            format!("unknown brick `{}`", symbol),
+                        ))
+                    }
+                }
+            }
+        }
+
+        if !bricks.iter().any(Brick::is_breakable) {
+            return Err(LevelError::new(
+                separator_line + 1,
+                1,
+                "the level has no bricks that can be broken",
+            ));
+        }
+
+        Ok(Level {
+            name,
+            ball_speed,
+            par_time,
+            bricks,
+            width,
+        })
+    }
+
+    /// The level's bricks laid out in a playfield of `dim`, centred across
+    /// it and a little below the top wall. `parse` makes sure every brick
+    /// fits the arena, so only a smaller playfield leaves any out.
+    pub fn bricks(&self, dim: GameDimension) -> Vec<Brick> {
+        let x = dim.1.saturating_sub(self.width) / 2;
+        let y = 1 + TOP_GAP;
+
+        self.bricks
+            .iter()
+            .map(|b| b.translated(x, y))
+            .filter(|b| b.fits_within(dim))
+            .collect()
+    }
+}
+
+/// What breaking each kind of brick scores.
+struct BrickPoints {
+    /// For each hit a standard brick takes.
+    standard: u32,
+    explosive: u32,
+}
+
+impl BrickPoints {
+    /// Points for a brick of `kind`, worth more the harder it is to break.
+    fn of(&self, kind: BrickKind) -> u32 {
+        match kind {
+            BrickKind::Standard(hits) => self.standard.saturating_mul(hits as u32),
+            BrickKind::Steel => 0,
+            BrickKind::Explosive => self.explosive,
+        }
+    }
+}
+
+/// A `WIDTH`x`HEIGHT` size such as `4x1`, neither of them zero.
+fn parse_size(value: &str) -> Option<(u16, u16)> {
+    let (width, height) = value.split_once('x')?;
+    let width = width.trim().parse::<u16>().ok()?;
+    let height = height.trim().parse::<u16>().ok()?;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The line and column `source` fails to parse at.
+    fn error_at(source: &str) -> (usize, usize) {
+        let error = Level::parse(source)
+            .err()
+            .expect("the level should not parse");
+        (error.line, error.column)
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        // Missing separator, reported on the last line
+        assert_eq!(error_at("name: Test\nball_speed: 2\n"), (2, 1));
+        // Unknown setting, at its key
+        assert_eq!(error_at("name: Test\n  colour: red\n---\n#\n"), (2, 3));
+        // Bad values, where the value starts
+        assert_eq!(
+            error_at("name: Test\nball_speed:   fast\n---\n#\n"),
+            (2, 15)
+        );
+        assert_eq!(error_at("name: Test\npar_time: -1\n---\n#\n"), (2, 11));
+        assert_eq!(error_at("name: Test\nbrick_size: 4\n---\n#\n"), (2, 13));
+        assert_eq!(
+            error_at("name: Test\nexplosive_points: 1.5\n---\n#\n"),
+            (2, 19)
+        );
+        // Unknown brick, at its character
+        assert_eq!(error_at("name: Test\n---\n##\n#?#\n"), (4, 2));
+        // Nothing to break, at the first row of the grid
+        assert_eq!(error_at("name: Test\n---\n==\n..\n"), (3, 1));
+    }
+
+    #[test]
+    fn points_can_be_set_per_kind_of_brick() {
+        let points = |source: &str| -> Vec<u32> {
+            let level = Level::parse(source).unwrap();
+            level.bricks(ARENA).iter().map(Brick::points).collect()
+        };
+        assert_eq!(points("name: Test\n---\n#3*=\n"), [10, 30, 10, 0]);
+        assert_eq!(
+            points("name: Test\nstandard_points: 25\nexplosive_points: 100\n---\n#3*=\n"),
+            [25, 75, 100, 0]
+        );
+    }
+
+    #[test]
+    fn grids_wider_than_the_arena_are_rejected() {
+        let row = "#".repeat(80);
+        assert_eq!(
+            error_at(&format!("name: Wide\nbrick_size: 1000x1\n---\n{}\n", row)),
+            (4, 1)
+        );
+        assert_eq!(error_at(&format!("name: Wide\n---\n#\n{}\n", row)), (4, 69));
+
+        let fits = "#".repeat(ARENA.1 as usize - 2);
+        assert!(Level::parse(&format!("name: Wide\n---\n{}\n", fits)).is_ok());
+    }
+
+    #[test]
+    fn grids_taller_than_the_arena_are_rejected() {
+        assert_eq!(
+            error_at("name: Tall\nbrick_size: 1x1000\n---\n..#\n"),
+            (4, 3)
+        );
+
+        let rows = "#\n".repeat(ARENA.0 as usize);
+        assert_eq!(
+            error_at(&format!("name: Tall\n---\n{}", rows)),
+            (3 + ARENA.0 as usize - 4, 1)
+        );
+    }
+
+    #[test]
+    fn parsed_bricks_all_fit_the_arena() {
+        let level = Level::parse("name: Grid\nbrick_size: 4x2\n---\n#..\n.=*\n").unwrap();
+        let bricks = level.bricks(ARENA);
+        assert_eq!(bricks.len(), 3);
+        assert!(bricks.iter().all(|b| b.fits_within(ARENA)));
+    }
+}
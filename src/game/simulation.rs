@@ -0,0 +1,814 @@
+use super::ball::Ball;
+use super::board::Board;
+use super::bolt::Bolt;
+use super::brick::Brick;
+use super::capsule::Capsule;
+use super::config::Config;
+use super::level::Level;
+use super::rng::Rng;
+use super::wall::{self, Wall};
+use super::{
+    Canvas, Collidable, GameDimension, GameObject, GamePhysics, MoveCommand, ObjectCoordinates,
+    Vector,
+};
+
+use std::f64::consts::PI;
+use std::io;
+use std::time::Duration;
+
+pub use super::capsule::PowerUp;
+
+/// Upper bound on bounces resolved for a ball within a single step.
+const MAX_IMPACTS_PER_STEP: usize = 8;
+
+/// Highest multiplier a combo can reach.
+const MAX_COMBO: u32 = 8;
+
+/// One in this many destroyed bricks drops a power-up.
+const DROP_CHANCE: usize = 5;
+
+/// Power-ups a brick can drop, equally likely.
+const DROPS: [PowerUp; 8] = [
+    PowerUp::Widen,
+    PowerUp::Shrink,
+    PowerUp::SlowBall,
+    PowerUp::FastBall,
+    PowerUp::ExtraLife,
+    PowerUp::MultiBall,
+    PowerUp::Sticky,
+    PowerUp::Laser,
+];
+
+/// How long a timed power-up lasts.
+const EFFECT_TIME: Duration = Duration::from_secs(15);
+
+/// Paddle width while `Widen` or `Shrink` is active, relative to normal.
+const WIDE_BOARD: f64 = 1.6;
+const NARROW_BOARD: f64 = 0.6;
+
+/// Ball speed while `SlowBall` or `FastBall` is active, relative to the
+/// level's.
+const SLOW_BALL: f64 = 0.6;
+const FAST_BALL: f64 = 1.5;
+
+/// Angle either side of the first ball that `MultiBall` sends new balls
+/// off at.
+const MULTI_BALL_SPREAD: f64 = PI / 6.0;
+
+/// How long the laser needs between shots.
+const LASER_RELOAD: Duration = Duration::from_millis(200);
+
+/// Seed for brick drops, so that runs can be replayed.
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// What the player asked for during a single step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Input {
+    /// Move the paddle left. Cancelled out by `right`.
+    pub left: bool,
+    pub right: bool,
+    /// Send off any balls resting on the paddle.
+    pub launch: bool,
+    /// Shoot from the paddle, if it has a laser.
+    pub fire: bool,
+}
+
+/// Things that happened during a step that the outside world may care about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// A brick was destroyed, scoring `points` after the combo multiplier.
+    BrickDestroyed { points: u32 },
+    /// The last ball in play fell past the paddle and a life was lost.
+    BallLost,
+    /// The last life was lost.
+    GameOver,
+    /// Every brick has been destroyed.
+    LevelCleared,
+    /// The paddle caught a power-up capsule.
+    PowerUpCollected { kind: PowerUp },
+}
+
+/// How the current level has gone so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelStats {
+    /// Steps played on this level.
+    pub steps: u64,
+    pub bricks_destroyed: usize,
+    /// Score gained on this level.
+    pub points: u32,
+}
+
+/// Something a ball or bolt can run into.
+#[derive(Clone, Copy)]
+enum Target {
+    Wall(usize),
+    DeathZone,
+    Board,
+    Brick(usize),
+}
+
+/// The game world without a terminal attached.
+///
+/// Everything is sized from an explicit `GameDimension` and only changes
+/// when `step` is called, so it can be driven from tests, bots or replays.
+pub struct Simulation {
+    /// Balls in play. There is always at least one.
+    balls: Vec<Ball>,
+    board: Board,
+    walls: [Wall; 3],
+    death_zone: Wall,
+    bricks: Vec<Brick>,
+    capsules: Vec<Capsule>,
+    bolts: Vec<Bolt>,
+    /// Steps until the laser can fire again.
+    reload: u32,
+    /// Timed power-ups in effect and the steps they have left.
+    effects: Vec<(PowerUp, u32)>,
+    /// The level's ball speed as scaled by the config, in cells per step,
+    /// before any power-ups.
+    ball_speed: f64,
+    config: Config,
+    rng: Rng,
+    dim: GameDimension,
+    lives: u32,
+    score: u32,
+    /// Bricks hit since the ball last touched the paddle.
+    combo: u32,
+    /// Steps played on the current level.
+    level_steps: u64,
+    /// Score when the current level started.
+    level_start_score: u32,
+}
+
+impl Simulation {
+    pub fn new(dim: GameDimension, level: &Level, config: &Config) -> Self {
+        let color = config.colors.walls;
+        let mut sim = Simulation {
+            dim,
+            balls: Vec::new(),
+            board: Board::new(dim, config),
+            walls: [
+                Wall::new(dim, wall::Direction::Left, color),
+                Wall::new(dim, wall::Direction::Right, color),
+                Wall::new(dim, wall::Direction::Top, color),
+            ],
+            death_zone: Wall::new(dim, wall::Direction::Bottom, color),
+            bricks: level.bricks(dim),
+            capsules: Vec::new(),
+            bolts: Vec::new(),
+            reload: 0,
+            effects: Vec::new(),
+            ball_speed: level.ball_speed * config.ball.speed / config.tick_rate as f64,
+            config: config.clone(),
+            rng: Rng::new(SEED),
+            lives: config.lives,
+            score: 0,
+            combo: 0,
+            level_steps: 0,
+            level_start_score: 0,
+        };
+        sim.serve();
+        sim
+    }
+
+    /// Swap in the bricks of `level` and start it with a fresh ball and
+    /// paddle. Score and lives carry over.
+    pub fn load_level(&mut self, level: &Level) {
+        self.board = Board::new(self.dim, &self.config);
+        self.bricks = level.bricks(self.dim);
+        self.capsules.clear();
+        self.bolts.clear();
+        self.effects.clear();
+        self.ball_speed = level.ball_speed * self.config.ball.speed / self.config.tick_rate as f64;
+        self.combo = 0;
+        self.level_steps = 0;
+        self.level_start_score = self.score;
+        self.balls.clear();
+        self.serve();
+    }
+
+    /// Put a new ball on the paddle, a little right of the middle so that
+    /// it goes off at an angle if launched straight away.
+    fn serve(&mut self) {
+        let ObjectCoordinates(x1, y1, x2, _) = self.board.get_coordinates();
+        let x = x1 + (x2 - x1) * 0.75;
+        let mut ball = Ball::new(x, y1, self.ball_speed, self.config.colors.ball, self.dim);
+        ball.hold(&self.board);
+        self.balls.push(ball);
+    }
+
+    pub fn level_stats(&self) -> LevelStats {
+        LevelStats {
+            steps: self.level_steps,
+            bricks_destroyed: self.bricks.iter().filter(|b| b.is_destroyed()).count(),
+            points: self.score - self.level_start_score,
+        }
+    }
+
+    pub fn dim(&self) -> GameDimension {
+        self.dim
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.lives
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// What the next brick's points will be multiplied by.
+    pub fn multiplier(&self) -> u32 {
+        (self.combo + 1).min(MAX_COMBO)
+    }
+
+    /// Balls in play, including any resting on the paddle.
+    pub fn balls(&self) -> impl Iterator<Item = &dyn Collidable> {
+        self.balls.iter().map(|b| b as &dyn Collidable)
+    }
+
+    pub fn paddle(&self) -> &dyn Collidable {
+        &self.board
+    }
+
+    /// Bricks still standing, including ones that can't be broken.
+    pub fn bricks(&self) -> impl Iterator<Item = &dyn Collidable> {
+        self.bricks
+            .iter()
+            .filter(|b| !b.is_destroyed())
+            .map(|b| b as &dyn Collidable)
+    }
+
+    /// Timed power-ups in effect and the steps each has left.
+    pub fn effects(&self) -> &[(PowerUp, u32)] {
+        &self.effects
+    }
+
+    /// Whether every life has been lost. The world no longer changes then.
+    pub fn is_over(&self) -> bool {
+        self.lives == 0
+    }
+
+    /// Advance the world by one step.
+    pub fn step(&mut self, input: Input) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.is_over() {
+            return events;
+        }
+
+        self.level_steps += 1;
+
+        match (input.left, input.right) {
+            (true, false) => self.board.move_left(),
+            (false, true) => self.board.move_right(),
+            _ => (),
+        }
+        if input.fire {
+            self.fire();
+        }
+        self.reload = self.reload.saturating_sub(1);
+        for ball in &mut self.balls {
+            ball.follow(&self.board);
+            if input.launch {
+                ball.launch(self.board.aim(ball));
+            }
+        }
+
+        self.tick_effects();
+        let lost: Vec<usize> = (0..self.balls.len())
+            .filter(|&b| !self.balls[b].is_held() && self.move_ball(b, &mut events))
+            .collect();
+        // From the back, so the other indices stay put, and before catching
+        // capsules, so a multi-ball only splits balls still in play
+        for b in lost.into_iter().rev() {
+            self.balls.remove(b);
+        }
+        self.move_capsules(&mut events);
+        self.move_bolts(&mut events);
+
+        if self.balls.is_empty() {
+            self.lives -= 1;
+            self.combo = 0;
+            self.capsules.clear();
+            self.bolts.clear();
+            self.effects.clear();
+            self.apply_effects();
+            events.push(GameEvent::BallLost);
+
+            self.serve();
+            if self.is_over() {
+                events.push(GameEvent::GameOver);
+            }
+        }
+        self.board.update_object();
+
+        events
+    }
+
+    /// Sweep ball `b` along its velocity, bouncing off whatever it reaches
+    /// first and carrying on with the rest of the step from there, so it
+    /// cannot skip over anything however fast it goes.
+    ///
+    /// Returns whether the ball fell into the death zone.
+    fn move_ball(&mut self, b: usize, events: &mut Vec<GameEvent>) -> bool {
+        // Fraction of this step's motion still left to travel
+        let mut remaining = 1.0;
+        let sticky = self.is_active(PowerUp::Sticky);
+
+        for _ in 0..MAX_IMPACTS_PER_STEP {
+            let Vector(vx, vy) = self.balls[b].get_velocity();
+            let motion = Vector(vx * remaining, vy * remaining);
+
+            let Some((time, target)) = self.earliest_impact(&self.balls[b], &motion) else {
+                self.balls[b].travel(remaining);
+                return false;
+            };
+
+            let ball = &mut self.balls[b];
+            ball.travel(remaining * time);
+            match target {
+                Target::Wall(i) => ball.handle_collision(&self.walls[i]),
+                Target::DeathZone => return true,
+                Target::Board => {
+                    ball.handle_collision(&self.board);
+                    self.combo = 0;
+                    // Only catch balls landing on top, not clipping an end
+                    if sticky && ball.get_velocity().1 < 0.0 {
+                        ball.hold(&self.board);
+                        return false;
+                    }
+                }
+                Target::Brick(i) => {
+                    self.bricks[i].handle_collision(ball);
+                    ball.handle_collision(&self.bricks[i]);
+                    self.brick_hit(i, events);
+                }
+            }
+            remaining *= 1.0 - time;
+        }
+        false
+    }
+
+    /// Shoot a bolt up from either end of the paddle, if it has a laser and
+    /// has reloaded.
+    fn fire(&mut self) {
+        if !self.is_active(PowerUp::Laser) || self.reload > 0 {
+            return;
+        }
+        let ObjectCoordinates(x1, y1, x2, _) = self.board.get_coordinates();
+        let tick_rate = self.config.tick_rate;
+        self.bolts.push(Bolt::new(x1, y1 - 1.0, tick_rate));
+        self.bolts.push(Bolt::new(x2 - 1.0, y1 - 1.0, tick_rate));
+        self.reload = self.steps(LASER_RELOAD);
+    }
+
+    /// Send bolts up until they hit a brick or the top wall.
+    fn move_bolts(&mut self, events: &mut Vec<GameEvent>) {
+        for b in 0..self.bolts.len() {
+            let motion = self.bolts[b].get_velocity();
+            let Some((time, target)) = self.earliest_impact(&self.bolts[b], &motion) else {
+                self.bolts[b].update_object();
+                continue;
+            };
+
+            let bolt = &mut self.bolts[b];
+            bolt.travel(time);
+            match target {
+                Target::Brick(i) => {
+                    self.bricks[i].handle_collision(bolt);
+                    bolt.handle_collision(&self.bricks[i]);
+                    self.brick_hit(i, events);
+                }
+                Target::Wall(i) => bolt.handle_collision(&self.walls[i]),
+                Target::DeathZone => bolt.handle_collision(&self.death_zone),
+                Target::Board => bolt.handle_collision(&self.board),
+            }
+        }
+        self.bolts.retain(|b| !b.is_spent());
+    }
+
+    /// Follow up on brick `i` having been hit.
+    fn brick_hit(&mut self, i: usize, events: &mut Vec<GameEvent>) {
+        if self.bricks[i].is_destroyed() {
+            self.brick_destroyed(i, events);
+            if self.is_cleared() {
+                events.push(GameEvent::LevelCleared);
+            }
+        }
+    }
+
+    /// Score brick `i`, which was just destroyed, and set off the bricks
+    /// around it if it explodes.
+    fn brick_destroyed(&mut self, i: usize, events: &mut Vec<GameEvent>) {
+        let points = self.bricks[i].points() * self.multiplier();
+        self.score += points;
+        self.combo += 1;
+        events.push(GameEvent::BrickDestroyed { points });
+
+        if self.rng.one_in(DROP_CHANCE) {
+            let ObjectCoordinates(x1, _, x2, y2) = self.bricks[i].get_coordinates();
+            let kind = DROPS[self.rng.below(DROPS.len())];
+            let x = (x1 + x2) / 2.0;
+            let capsule = Capsule::new(x, y2 - 1.0, kind, self.dim, self.config.tick_rate);
+            self.capsules.push(capsule);
+        }
+
+        let caught: Vec<usize> = (0..self.bricks.len())
+            .filter(|&j| {
+                let other = &self.bricks[j];
+                other.is_breakable() && !other.is_destroyed() && self.bricks[i].blast_reaches(other)
+            })
+            .collect();
+        for j in caught {
+            // An earlier blast in the chain may have got it already
+            if self.bricks[j].is_destroyed() {
+                continue;
+            }
+            self.bricks[j].destroy();
+            self.brick_destroyed(j, events);
+        }
+    }
+
+    /// Let capsules fall, handing out the power-ups the paddle catches.
+    fn move_capsules(&mut self, events: &mut Vec<GameEvent>) {
+        for capsule in &mut self.capsules {
+            capsule.update_object();
+            capsule.handle_collision(&self.board);
+        }
+
+        let caught: Vec<PowerUp> = self
+            .capsules
+            .iter()
+            .filter(|c| c.is_caught())
+            .map(|c| c.kind())
+            .collect();
+        self.capsules.retain(|c| !c.is_caught() && !c.is_lost());
+
+        for kind in caught {
+            self.activate(kind);
+            events.push(GameEvent::PowerUpCollected { kind });
+        }
+    }
+
+    fn activate(&mut self, kind: PowerUp) {
+        if kind == PowerUp::ExtraLife {
+            self.lives += 1;
+        }
+        if let (PowerUp::MultiBall, Some(ball)) = (kind, self.balls.first()) {
+            let extra = [
+                ball.rotated(MULTI_BALL_SPREAD),
+                ball.rotated(-MULTI_BALL_SPREAD),
+            ];
+            self.balls.extend(extra);
+        }
+        if kind.is_timed() {
+            // Catching one again starts it over
+            self.effects
+                .retain(|(active, _)| *active != kind && Some(*active) != kind.opposite());
+            self.effects.push((kind, self.steps(EFFECT_TIME)));
+        }
+        self.apply_effects();
+    }
+
+    /// Count down timed power-ups, undoing the ones that run out.
+    fn tick_effects(&mut self) {
+        let before = self.effects.len();
+        for (_, steps) in &mut self.effects {
+            *steps -= 1;
+        }
+        self.effects.retain(|(_, steps)| *steps > 0);
+        if self.effects.len() != before {
+            self.apply_effects();
+        }
+    }
+
+    /// Steps it takes for `time` to pass, at least one.
+    fn steps(&self, time: Duration) -> u32 {
+        let steps = (time.as_secs_f64() * self.config.tick_rate as f64).ceil();
+        (steps as u32).max(1)
+    }
+
+    fn is_active(&self, kind: PowerUp) -> bool {
+        self.effects.iter().any(|(active, _)| *active == kind)
+    }
+
+    /// Size the paddle and speed up the balls for whatever is in effect.
+    fn apply_effects(&mut self) {
+        let width = if self.is_active(PowerUp::Widen) {
+            WIDE_BOARD
+        } else if self.is_active(PowerUp::Shrink) {
+            NARROW_BOARD
+        } else {
+            1.0
+        };
+        self.board
+            .set_width((self.config.paddle.width as f64 * width).round() as u16);
+
+        let speed = if self.is_active(PowerUp::SlowBall) {
+            SLOW_BALL
+        } else if self.is_active(PowerUp::FastBall) {
+            FAST_BALL
+        } else {
+            1.0
+        };
+        for ball in &mut self.balls {
+            ball.set_speed(self.ball_speed * speed);
+        }
+    }
+
+    /// Whether every brick that can be broken has been.
+    fn is_cleared(&self) -> bool {
+        self.bricks
+            .iter()
+            .all(|b| b.is_destroyed() || !b.is_breakable())
+    }
+
+    /// The first thing `mover`, moving by `motion`, runs into, and the
+    /// fraction of `motion` until it does.
+    fn earliest_impact(&self, mover: &dyn Collidable, motion: &Vector) -> Option<(f64, Target)> {
+        let walls = self
+            .walls
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w as &dyn Collidable, Target::Wall(i)));
+        let bricks = self
+            .bricks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (b as &dyn Collidable, Target::Brick(i)));
+
+        walls
+            .chain([(&self.death_zone as &dyn Collidable, Target::DeathZone)])
+            .chain(bricks)
+            .chain([(&self.board as &dyn Collidable, Target::Board)])
+            .filter_map(|(object, target)| {
+                object
+                    .time_of_impact(mover, motion)
+                    .map(|time| (time, target))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+
+    pub fn draw(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
+        for ball in &self.balls {
+            ball.draw_object(canvas)?;
+        }
+        for w in &self.walls {
+            w.draw_object(canvas)?;
+        }
+        for b in &self.bricks {
+            b.draw_object(canvas)?;
+        }
+        for c in &self.capsules {
+            c.draw_object(canvas)?;
+        }
+        for b in &self.bolts {
+            b.draw_object(canvas)?;
+        }
+        self.board.draw_object(canvas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL: &str = "name: Test\n---\n####\n";
+
+    const LAUNCH: Input = Input {
+        left: false,
+        right: false,
+        launch: true,
+        fire: false,
+    };
+
+    fn simulation(dim: GameDimension) -> Simulation {
+        let level = Level::parse(LEVEL).unwrap();
+        Simulation::new(dim, &level, &Config::default())
+    }
+
+    #[test]
+    fn small_playfields_do_not_panic() {
+        for dim in [
+            GameDimension(15, 70),
+            GameDimension(6, 8),
+            GameDimension(3, 3),
+        ] {
+            let mut sim = simulation(dim);
+            sim.step(LAUNCH);
+            for i in 0..200 {
+                let input = Input {
+                    left: i % 3 == 0,
+                    right: i % 3 == 1,
+                    ..Input::default()
+                };
+                sim.step(input);
+            }
+        }
+    }
+
+    #[test]
+    fn ball_rests_on_the_paddle_until_launched() {
+        let mut sim = simulation(GameDimension(24, 70));
+        let ObjectCoordinates(px1, py1, px2, _) = sim.paddle().get_coordinates();
+        assert_eq!(py1, 23.0);
+
+        let ball = sim.balls().next().unwrap().get_coordinates();
+        assert_eq!(sim.balls().count(), 1);
+        assert_eq!(ball.3, py1);
+        assert!(px1 <= ball.0 && ball.2 <= px2);
+
+        sim.step(LAUNCH);
+        let ball = sim.balls().next().unwrap();
+        assert!(ball.get_coordinates().3 < py1);
+        assert!(ball.get_velocity().1 < 0.0);
+    }
+
+    #[test]
+    fn multi_ball_splits_a_ball_still_in_play() {
+        let dim = GameDimension(24, 70);
+        let mut sim = simulation(dim);
+        let ObjectCoordinates(px1, py1, _, _) = sim.paddle().get_coordinates();
+
+        // The first ball drops into the death zone away from the paddle in
+        // the same step the paddle catches a multi-ball
+        let color = sim.config.colors.ball;
+        let falling = Ball::new(2.0, py1, 1.0, color, dim).rotated(std::f64::consts::PI);
+        let rising = Ball::new(35.0, 10.0, 1.0, color, dim);
+        sim.balls = vec![falling, rising];
+        sim.capsules = vec![Capsule::new(
+            px1 + 2.0,
+            py1 - 1.0,
+            PowerUp::MultiBall,
+            dim,
+            25,
+        )];
+
+        let events = sim.step(Input::default());
+        assert!(events.contains(&GameEvent::PowerUpCollected {
+            kind: PowerUp::MultiBall
+        }));
+        assert_eq!(sim.balls().count(), 3);
+        assert!(sim.balls().all(|b| b.get_coordinates().3 < py1));
+    }
+
+    #[test]
+    fn bolt_can_clear_the_level_as_the_last_ball_drops() {
+        let dim = GameDimension(24, 70);
+        let level = Level::parse("name: Test\n---\n#\n").unwrap();
+        let mut sim = Simulation::new(dim, &level, &Config::default());
+        let ObjectCoordinates(bx1, _, _, by2) = sim.bricks().next().unwrap().get_coordinates();
+        let py1 = sim.paddle().get_coordinates().1;
+
+        let color = sim.config.colors.ball;
+        let falling = Ball::new(2.0, py1, 1.0, color, dim).rotated(std::f64::consts::PI);
+        sim.balls = vec![falling];
+        sim.bolts = vec![Bolt::new(bx1, by2, 25)];
+        sim.lives = 1;
+
+        let events = sim.step(Input::default());
+        assert!(events.contains(&GameEvent::LevelCleared));
+        assert!(events.contains(&GameEvent::GameOver));
+        assert!(sim.is_over());
+        assert!(sim.step(LAUNCH).is_empty());
+    }
+
+    #[test]
+    fn speeds_are_the_same_at_any_tick_rate() {
+        let level = Level::parse(LEVEL).unwrap();
+        let after_a_tenth_of_a_second = |tick_rate: u32| {
+            let config = Config {
+                tick_rate,
+                ..Config::default()
+            };
+            let mut sim = Simulation::new(GameDimension(24, 70), &level, &config);
+            let steps = tick_rate / 10;
+            let left = Input {
+                left: true,
+                ..Input::default()
+            };
+            for _ in 0..steps {
+                sim.step(left);
+            }
+            sim.step(LAUNCH);
+            for _ in 1..steps {
+                sim.step(Input::default());
+            }
+            sim.activate(PowerUp::Laser);
+            let effect_time = sim.effects[0].1 as f64 / tick_rate as f64;
+            let paddle = sim.paddle().get_coordinates();
+            let ball = sim.balls().next().unwrap().get_coordinates();
+            (paddle.0, ball.0, ball.1, effect_time)
+        };
+
+        let (paddle, ball_x, ball_y, effect_time) = after_a_tenth_of_a_second(20);
+        assert_eq!(effect_time, 15.0);
+        for tick_rate in [40, 50, 100] {
+            let other = after_a_tenth_of_a_second(tick_rate);
+            assert!((other.0 - paddle).abs() < 1e-6, "paddle at {}", tick_rate);
+            assert!((other.1 - ball_x).abs() < 1e-6, "ball at {}", tick_rate);
+            assert!((other.2 - ball_y).abs() < 1e-6, "ball at {}", tick_rate);
+            assert_eq!(other.3, effect_time);
+        }
+    }
+
+    /// A simulation of a level with `grid` for its bricks.
+    fn level(grid: &str) -> Simulation {
+        let level = Level::parse(&format!("name: Test\n---\n{}", grid)).unwrap();
+        Simulation::new(GameDimension(24, 70), &level, &Config::default())
+    }
+
+    /// Step with a bolt fired at brick `i` from right below it.
+    fn hit(sim: &mut Simulation, i: usize) -> Vec<GameEvent> {
+        let ObjectCoordinates(x1, _, _, y2) = sim.bricks[i].get_coordinates();
+        sim.bolts = vec![Bolt::new(x1, y2, sim.config.tick_rate)];
+        sim.step(Input::default())
+    }
+
+    /// Points scored by each brick destroyed in `events`.
+    fn points(events: &[GameEvent]) -> Vec<u32> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::BrickDestroyed { points } => Some(*points),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn destroyed(sim: &Simulation) -> Vec<bool> {
+        sim.bricks.iter().map(Brick::is_destroyed).collect()
+    }
+
+    #[test]
+    fn explosive_bricks_take_their_neighbours() {
+        // The explosive is below and between the first two, and three cells
+        // short of the last
+        let mut sim = level("#.#..#\n.*....\n");
+        let events = hit(&mut sim, 3);
+
+        assert_eq!(destroyed(&sim), [true, true, false, true]);
+        // Chained bricks count towards the combo like any other
+        assert_eq!(points(&events), [10, 20, 30]);
+        assert_eq!(sim.score(), 60);
+    }
+
+    #[test]
+    fn explosions_set_each_other_off() {
+        let mut sim = level("*.*.*.#..#\n");
+        let events = hit(&mut sim, 0);
+
+        assert_eq!(destroyed(&sim), [true, true, true, true, false]);
+        assert_eq!(points(&events), [10, 20, 30, 40]);
+        assert!(!events.contains(&GameEvent::LevelCleared));
+    }
+
+    #[test]
+    fn steel_survives_blasts_and_is_not_needed_to_clear() {
+        let mut sim = level("=*#=\n");
+        let events = hit(&mut sim, 1);
+
+        assert_eq!(destroyed(&sim), [false, true, true, false]);
+        assert_eq!(points(&events), [10, 20]);
+        assert!(events.contains(&GameEvent::LevelCleared));
+    }
+
+    #[test]
+    fn dropping_the_ball_costs_a_life() {
+        let dim = GameDimension(24, 70);
+        let mut sim = simulation(dim);
+        let lives = sim.lives();
+        assert_eq!(lives, Config::default().lives);
+
+        for life in (0..lives).rev() {
+            // Away from the paddle, about to fall into the death zone
+            let py1 = sim.paddle().get_coordinates().1;
+            let color = sim.config.colors.ball;
+            sim.balls = vec![Ball::new(2.0, py1, 1.0, color, dim).rotated(std::f64::consts::PI)];
+
+            let events = sim.step(Input::default());
+            assert_eq!(sim.lives(), life);
+            assert_eq!(events.contains(&GameEvent::GameOver), life == 0);
+            assert!(events.contains(&GameEvent::BallLost));
+
+            // A fresh ball waits on the paddle
+            assert_eq!(sim.balls.len(), 1);
+            assert!(sim.balls[0].is_held());
+            assert_eq!(sim.balls().next().unwrap().get_coordinates().3, py1);
+        }
+
+        assert!(sim.is_over());
+        // Nothing moves any more, not even when launched
+        assert!(sim.step(LAUNCH).is_empty());
+        assert!(sim.balls[0].is_held());
+        assert_eq!(sim.lives(), 0);
+    }
+
+    #[test]
+    fn bricks_are_laid_out_below_the_top_wall() {
+        let sim = simulation(GameDimension(24, 70));
+        let bricks: Vec<ObjectCoordinates> = sim.bricks().map(|b| b.get_coordinates()).collect();
+        assert_eq!(bricks.len(), 4);
+        assert!(bricks.iter().all(|b| b.1 == 3.0 && b.2 - b.0 == 1.0));
+    }
+}
@@ -0,0 +1,468 @@
+use super::canvas::{Dimmed, Viewport};
+use super::hud::HUD_HEIGHT;
+use super::keymap::{key_name, Action, KeyMap};
+use super::simulation::GameEvent;
+use super::text::{HorizontalAlign, Position, Text, VerticalAlign};
+use super::{Canvas, Game, GameObject};
+use crossterm::event::KeyCode;
+
+use std::io;
+use std::time::Instant;
+
+/// Which screen the game is on.
+///
+/// Each state decides how it reacts to keys and ticks by returning the state
+/// to move to, and draws itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameState {
+    Title,
+    /// The ball rests on the paddle until the player launches it.
+    Serve,
+    Playing,
+    /// Frozen with a menu on top, to go back to `from` on resuming.
+    Paused {
+        from: Phase,
+        selected: PauseOption,
+    },
+    LevelComplete,
+    GameOver,
+    /// The controls, over the game paused in `from`, or the title screen.
+    Help {
+        from: Option<Phase>,
+    },
+    Quit,
+}
+
+/// The states a game in progress can be paused in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Serve,
+    Playing,
+}
+
+impl Phase {
+    fn state(self) -> GameState {
+        match self {
+            Phase::Serve => GameState::Serve,
+            Phase::Playing => GameState::Playing,
+        }
+    }
+}
+
+/// Entries of the pause menu, from top to bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseOption {
+    Resume,
+    Restart,
+    Quit,
+}
+
+impl PauseOption {
+    const ALL: [PauseOption; 3] = [PauseOption::Resume, PauseOption::Restart, PauseOption::Quit];
+
+    fn label(&self) -> &'static str {
+        match self {
+            PauseOption::Resume => "Resume",
+            PauseOption::Restart => "Restart",
+            PauseOption::Quit => "Quit",
+        }
+    }
+
+    fn next(self) -> PauseOption {
+        let i = Self::ALL.iter().position(|o| *o == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn previous(self) -> PauseOption {
+        let i = Self::ALL.iter().position(|o| *o == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+impl GameState {
+    /// The phase of the game in progress, if it isn't paused or over.
+    fn phase(self) -> Option<Phase> {
+        match self {
+            GameState::Serve => Some(Phase::Serve),
+            GameState::Playing => Some(Phase::Playing),
+            _ => None,
+        }
+    }
+
+    /// The pause menu over the game in `from`, with the cursor on Resume.
+    fn paused(from: Phase) -> GameState {
+        GameState::Paused {
+            from,
+            selected: PauseOption::Resume,
+        }
+    }
+
+    /// The terminal window lost focus; don't keep playing unattended.
+    pub fn on_focus_lost(self) -> GameState {
+        match self.phase() {
+            Some(from) => GameState::paused(from),
+            None => self,
+        }
+    }
+
+    pub fn on_key(self, game: &mut Game, key: KeyCode) -> GameState {
+        let action = game.config.keys.action(key);
+
+        // The pause menu is always worked with the arrows and Enter
+        if let GameState::Paused { from, selected } = self {
+            match key {
+                KeyCode::Up => {
+                    let selected = selected.previous();
+                    return GameState::Paused { from, selected };
+                }
+                KeyCode::Down => {
+                    let selected = selected.next();
+                    return GameState::Paused { from, selected };
+                }
+                KeyCode::Enter => {
+                    return match selected {
+                        PauseOption::Resume => from.state(),
+                        PauseOption::Restart => {
+                            game.restart();
+                            GameState::Serve
+                        }
+                        PauseOption::Quit => GameState::Quit,
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // Enter moves on from the other screens, as well as launch
+        let confirm = key == KeyCode::Enter || action == Some(Action::Launch);
+
+        match (self, action) {
+            (GameState::Quit, _) => GameState::Quit,
+            (_, Some(Action::Quit)) => GameState::Quit,
+
+            // Any other key goes back, to the pause menu if a game was going on
+            (GameState::Help { from: Some(from) }, _) => GameState::paused(from),
+            (GameState::Help { from: None }, _) => GameState::Title,
+            (GameState::Title, Some(Action::Help)) => GameState::Help { from: None },
+            (GameState::Serve | GameState::Playing, Some(Action::Help)) => {
+                GameState::Help { from: self.phase() }
+            }
+            (GameState::Paused { from, .. }, Some(Action::Help)) => {
+                GameState::Help { from: Some(from) }
+            }
+
+            (GameState::Title, _) if confirm => GameState::Serve,
+            (GameState::Serve | GameState::Playing, Some(Action::Launch)) => {
+                game.input.launch = true;
+                GameState::Playing
+            }
+            (
+                GameState::Serve | GameState::Playing,
+                Some(action @ (Action::MoveLeft | Action::MoveRight)),
+            ) => {
+                game.held.press(action, key, Instant::now());
+                self
+            }
+            (GameState::Serve | GameState::Playing, Some(Action::Fire)) => {
+                game.input.fire = true;
+                self
+            }
+            (GameState::Serve, Some(Action::Pause)) => GameState::paused(Phase::Serve),
+            (GameState::Playing, Some(Action::Pause)) => GameState::paused(Phase::Playing),
+
+            (GameState::Paused { from, .. }, Some(Action::Pause | Action::Launch)) => from.state(),
+
+            (GameState::LevelComplete, _) if confirm => {
+                if game.next_level() {
+                    GameState::Serve
+                } else {
+                    game.restart();
+                    GameState::Title
+                }
+            }
+            (GameState::GameOver, _) if confirm => {
+                game.restart();
+                GameState::Title
+            }
+
+            _ => self,
+        }
+    }
+
+    /// Advance by one tick.
+    pub fn update(self, game: &mut Game) -> GameState {
+        if self != GameState::Serve && self != GameState::Playing {
+            return self;
+        }
+
+        let mut input = std::mem::take(&mut game.input);
+        match game.held.direction(Instant::now()) {
+            Some(Action::MoveLeft) => input.left = true,
+            Some(Action::MoveRight) => input.right = true,
+            _ => (),
+        }
+        let events = game.sim.step(input);
+        self.after_step(&events)
+    }
+
+    /// Where a step that reported `events` leaves the game. Losing the last
+    /// life counts for more than clearing the level in the same step.
+    fn after_step(self, events: &[GameEvent]) -> GameState {
+        if events.contains(&GameEvent::GameOver) {
+            GameState::GameOver
+        } else if events.contains(&GameEvent::LevelCleared) {
+            GameState::LevelComplete
+        } else if events.contains(&GameEvent::BallLost) {
+            GameState::Serve
+        } else {
+            self
+        }
+    }
+
+    pub fn draw(&self, game: &Game, canvas: &mut dyn Canvas) -> io::Result<()> {
+        if *self == GameState::Quit {
+            return Ok(());
+        }
+
+        if let GameState::Paused { .. } | GameState::Help { .. } = self {
+            draw_world(game, &mut Dimmed::new(canvas))?;
+        } else {
+            draw_world(game, canvas)?;
+        }
+
+        let keys = &game.config.keys;
+        let message = match self {
+            GameState::Title => format!(
+                "B R E A K I T\n\n{}",
+                hints(&[
+                    (keys.hint(Action::Launch), "start"),
+                    (keys.hint(Action::Help), "controls"),
+                    (keys.hint(Action::Quit), "quit"),
+                ])
+            ),
+            GameState::Serve => hints(&[
+                (
+                    keys.hint(Action::MoveLeft) + &keys.hint(Action::MoveRight),
+                    "aim",
+                ),
+                (keys.hint(Action::Launch), "launch"),
+            ]),
+            GameState::Playing => return Ok(()),
+            GameState::Paused { selected, .. } => {
+                let mut menu = String::from("Paused\n");
+                for option in PauseOption::ALL {
+                    let cursor = if option == *selected { ">" } else { " " };
+                    menu.push_str(&format!("\n{} {}", cursor, option.label()));
+                }
+                menu
+            }
+            GameState::LevelComplete => level_summary(game),
+            GameState::GameOver => format!(
+                "Game Over\nScore {}\n\n{}",
+                game.sim.score(),
+                hints(&[
+                    (keys.hint(Action::Launch), "play again"),
+                    (keys.hint(Action::Quit), "quit"),
+                ])
+            ),
+            GameState::Help { .. } => controls(keys),
+            GameState::Quit => unreachable!(),
+        };
+
+        Text::new(
+            &message,
+            Position(HorizontalAlign::Centre, VerticalAlign::Centre),
+            game.dim,
+        )
+        .draw_object(canvas)
+    }
+}
+
+/// How the level that was just cleared went, and what comes next.
+fn level_summary(game: &Game) -> String {
+    let stats = game.sim.level_stats();
+    let seconds = stats.steps / game.config.tick_rate as u64;
+    let time = match game.campaign.level().par_time {
+        Some(par) => format!("Time {}  (par {})", clock(seconds), clock(par.as_secs())),
+        None => format!("Time {}", clock(seconds)),
+    };
+
+    let (title, next) = if game.campaign.is_last() {
+        ("Campaign complete!", "play again")
+    } else {
+        ("Level complete!", "next level")
+    };
+    let keys = &game.config.keys;
+
+    format!(
+        "{}\n{}\n\n{}\nBricks {}\nPoints {}\nScore  {}\nLives  {}\n\n{}",
+        title,
+        game.campaign.level().name,
+        time,
+        stats.bricks_destroyed,
+        stats.points,
+        game.sim.score(),
+        game.sim.lives(),
+        hints(&[
+            (keys.hint(Action::Launch), next),
+            (keys.hint(Action::Quit), "quit")
+        ])
+    )
+}
+
+/// Lines of keys and what they do, with the descriptions lined up.
+fn hints(lines: &[(String, &str)]) -> String {
+    let width = lines
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|(key, what)| format!("{:<width$}  {}", key, what, width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Every action and all the keys bound to it.
+fn controls(keys: &KeyMap) -> String {
+    let width = Action::ALL
+        .iter()
+        .map(|a| a.label().len())
+        .max()
+        .unwrap_or(0);
+    let mut lines = vec!["Controls".to_string(), String::new()];
+    for action in Action::ALL {
+        let bound: Vec<String> = keys.keys(action).iter().map(|key| key_name(*key)).collect();
+        lines.push(format!(
+            "{:<width$}  {}",
+            action.label(),
+            bound.join(" "),
+            width = width
+        ));
+    }
+    lines.push(String::new());
+    lines.push(format!(
+        "{:<width$}  {}",
+        "Menus",
+        "↑ ↓ Enter",
+        width = width
+    ));
+    lines.push(String::new());
+    lines.push("Any key to go back".to_string());
+    lines.join("\n")
+}
+
+/// `seconds` as minutes and seconds, e.g. `2:05`.
+fn clock(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// The HUD and the playfield.
+fn draw_world(game: &Game, canvas: &mut dyn Canvas) -> io::Result<()> {
+    game.hud.draw(canvas)?;
+    game.sim.draw(&mut Viewport::new(canvas, 0, HUD_HEIGHT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::campaign::Campaign;
+    use super::super::config::Config;
+    use super::super::held::HeldKeys;
+    use super::super::hud::Hud;
+    use super::super::simulation::{Input, Simulation};
+    use super::super::{ARENA, MIN_SIZE, SCREEN};
+    use super::*;
+
+    /// A game as `Game::new` starts it, without asking a terminal its size.
+    fn game() -> Game {
+        let campaign = Campaign::builtin();
+        let config = Config::default();
+        Game {
+            sim: Simulation::new(ARENA, campaign.level(), &config),
+            hud: Hud::new(SCREEN),
+            state: GameState::Title,
+            input: Input::default(),
+            held: HeldKeys::new(),
+            dim: SCREEN,
+            terminal: MIN_SIZE,
+            campaign,
+            config,
+        }
+    }
+
+    /// The state after pressing each of `keys` in turn, from `state`.
+    fn press(game: &mut Game, state: GameState, keys: &[KeyCode]) -> GameState {
+        keys.iter()
+            .fold(state, |state, key| state.on_key(game, *key))
+    }
+
+    #[test]
+    fn resuming_goes_back_to_where_the_game_was_paused() {
+        let mut game = game();
+        let pause = KeyCode::Char('p');
+        for from in [GameState::Serve, GameState::Playing] {
+            assert_eq!(press(&mut game, from, &[pause, KeyCode::Enter]), from);
+            assert_eq!(press(&mut game, from, &[pause, pause]), from);
+            assert_eq!(press(&mut game, from, &[pause, KeyCode::Char(' ')]), from);
+            assert_eq!(
+                press(
+                    &mut game,
+                    from,
+                    &[pause, KeyCode::Down, KeyCode::Up, KeyCode::Enter]
+                ),
+                from
+            );
+            assert_eq!(
+                press(&mut game, from.on_focus_lost(), &[KeyCode::Enter]),
+                from
+            );
+            // Through the controls and back to the menu
+            assert_eq!(
+                press(
+                    &mut game,
+                    from,
+                    &[pause, KeyCode::Char('?'), KeyCode::Char('x'), pause]
+                ),
+                from
+            );
+        }
+        assert!(!game.input.launch);
+    }
+
+    #[test]
+    fn game_over_wins_over_clearing_the_level() {
+        // A bolt breaking the last brick as the last ball drops
+        let events = [
+            GameEvent::BrickDestroyed { points: 10 },
+            GameEvent::LevelCleared,
+            GameEvent::BallLost,
+            GameEvent::GameOver,
+        ];
+        assert_eq!(GameState::Playing.after_step(&events), GameState::GameOver);
+
+        let events = [GameEvent::LevelCleared, GameEvent::BallLost];
+        assert_eq!(
+            GameState::Playing.after_step(&events),
+            GameState::LevelComplete
+        );
+        let events = [GameEvent::BallLost];
+        assert_eq!(GameState::Playing.after_step(&events), GameState::Serve);
+        let events = [GameEvent::BrickDestroyed { points: 10 }];
+        assert_eq!(GameState::Serve.after_step(&events), GameState::Serve);
+    }
+
+    #[test]
+    fn controls_go_back_to_where_they_were_opened() {
+        let mut game = game();
+        let help = KeyCode::Char('?');
+        assert_eq!(
+            press(&mut game, GameState::Title, &[help, KeyCode::Enter]),
+            GameState::Title
+        );
+        assert_eq!(
+            press(&mut game, GameState::Serve, &[help, KeyCode::Enter]),
+            GameState::paused(Phase::Serve)
+        );
+    }
+}
@@ -1,14 +1,9 @@
-use super::{GameDimension, GameObject};
-use crossterm::{
-    cursor::{self, MoveTo},
-    style::{self, style, Stylize},
-    terminal, QueueableCommand,
-};
-use std::io::{self, stdout, Write};
+use super::{Canvas, GameDimension, GameObject};
+use crossterm::style::{self, style};
+use std::io;
 
 pub struct Text {
     content: String,
-    outlined: bool,
     pos: Position,
     dim: GameDimension,
 }
@@ -33,16 +28,25 @@ impl Text {
             content: content.to_string(),
             pos,
             dim,
-            outlined: false,
         }
     }
+
+    pub fn set_content(&mut self, content: &str) {
+        self.content.clear();
+        self.content.push_str(content);
+    }
 }
 
 impl GameObject for Text {
-    fn fill_object(&self, symbol: style::StyledContent<&str>) -> io::Result<()> {
-        let mut stdout = stdout();
-
-        let length = symbol.content().len() as u16;
+    fn fill_object(
+        &self,
+        canvas: &mut dyn Canvas,
+        symbol: style::StyledContent<&str>,
+    ) -> io::Result<()> {
+        // Every line gets its own row inside the box
+        let lines: Vec<&str> = symbol.content().lines().collect();
+        let length = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u16;
+        let height = lines.len() as u16;
 
         // No content
         if length == 0 {
@@ -53,67 +57,49 @@ impl GameObject for Text {
 
         let x_offset = match horizontal {
             HorizontalAlign::Left => 0,
-            HorizontalAlign::Centre => self.dim.1 / 2 - (length + 2) / 2,
-            HorizontalAlign::Right => self.dim.1 - length - 2,
+            HorizontalAlign::Centre => (self.dim.1 / 2).saturating_sub((length + 2) / 2),
+            HorizontalAlign::Right => self.dim.1.saturating_sub(length + 2),
         };
 
         let y_offset = match vertical {
             VerticalAlign::Top => 0,
-            VerticalAlign::Centre => self.dim.0 / 2 - 1,
-            VerticalAlign::Bottom => self.dim.0 - 3,
+            VerticalAlign::Centre => (self.dim.0 / 2).saturating_sub((height + 2) / 2),
+            VerticalAlign::Bottom => self.dim.0.saturating_sub(height + 2),
         };
 
         // Top border
-        stdout
-            .queue(MoveTo(x_offset, y_offset))?
-            .queue(style::PrintStyledContent(style("┌")))?;
+        canvas.put(x_offset, y_offset, style("┌"))?;
         for i in 1..=length {
-            stdout
-                .queue(MoveTo(x_offset + i as u16, y_offset))?
-                .queue(style::PrintStyledContent(style("─")))?;
+            canvas.put(x_offset + i, y_offset, style("─"))?;
+        }
+        canvas.put(x_offset + length + 1, y_offset, style("┐"))?;
+
+        // Middle, padded so nothing behind the box shows through
+        for (row, line) in (y_offset + 1..).zip(&lines) {
+            let line = format!("{:<width$}", line, width = length as usize);
+            canvas.put(x_offset, row, style("│"))?;
+            canvas.put(
+                x_offset + 1,
+                row,
+                style::StyledContent::new(*symbol.style(), &line),
+            )?;
+            canvas.put(x_offset + length + 1, row, style("│"))?;
         }
-        stdout
-            .queue(MoveTo(x_offset + (length + 1) as u16, y_offset))?
-            .queue(style::PrintStyledContent(style("┐")))?;
-
-        // Middle
-        stdout
-            .queue(MoveTo(x_offset as u16, y_offset + 1))?
-            .queue(style::PrintStyledContent(style("│")))?
-            .queue(MoveTo(x_offset + 1, y_offset + 1))?
-            .queue(style::PrintStyledContent(symbol))?
-            .queue(MoveTo(x_offset + (length + 1) as u16, y_offset + 1))?
-            .queue(style::PrintStyledContent(style("│")))?;
 
         // Bottom border
-        stdout
-            .queue(MoveTo(x_offset, y_offset + 2))?
-            .queue(style::PrintStyledContent(style("└")))?;
+        let bottom = y_offset + height + 1;
+        canvas.put(x_offset, bottom, style("└"))?;
         for i in 1..=length {
-            stdout
-                .queue(MoveTo(x_offset + i as u16, y_offset + 2))?
-                .queue(style::PrintStyledContent(style("─")))?;
+            canvas.put(x_offset + i, bottom, style("─"))?;
         }
-        stdout
-            .queue(MoveTo(x_offset + (length + 1) as u16, y_offset + 2))?
-            .queue(style::PrintStyledContent(style("┘")))?;
-
-        stdout.flush()?;
+        canvas.put(x_offset + length + 1, bottom, style("┘"))?;
 
         Ok(())
     }
 
-    fn draw_object(&self) -> io::Result<()> {
-        self.fill_object(style(&self.content));
-        Ok(())
+    fn draw_object(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
+        self.fill_object(canvas, style(&self.content))
     }
 
-    fn clear_object(&self) -> io::Result<()> {
-        self.fill_object(style(""));
-        Ok(())
-    }
-
-    fn move_object(&mut self) -> io::Result<()> {
-        Ok(())
-    }
+    fn move_object(&mut self) {}
 }
@@ -1,78 +1,75 @@
-use super::{Collidable, GameDimension, GameObject, ObjectCoordinates, Vector};
-use crossterm::{
-    cursor,
-    style::{self, style, Stylize},
-    QueueableCommand,
-};
-use std::cmp::*;
-use std::io::{self, stdout, Write};
+use super::{Canvas, Collidable, GameDimension, GameObject, ObjectCoordinates, Vector};
+use crossterm::style::{self, Color, Stylize};
+use std::io;
 
 pub enum Direction {
     Left,
     Right,
     Top,
+    /// Just below the playfield, out of sight
     Bottom,
 }
 
 pub struct Wall {
     normals: [Vector; 1],
     dir: Direction,
+    color: Color,
     dim: GameDimension,
 }
 
 impl Wall {
-    pub fn new(dim: GameDimension, dir: Direction) -> Self {
+    pub fn new(dim: GameDimension, dir: Direction, color: Color) -> Self {
         match dir {
             Direction::Left => Wall {
                 normals: [Vector(1.0, 0.0)],
                 dir,
+                color,
                 dim,
             },
             Direction::Right => Wall {
                 normals: [Vector(-1.0, 0.0)],
                 dir,
+                color,
                 dim,
             },
             Direction::Top => Wall {
-                normals: [Vector(0.0, -1.0)],
+                normals: [Vector(0.0, 1.0)],
                 dir,
+                color,
                 dim,
             },
             Direction::Bottom => Wall {
-                normals: [Vector(0.0, 1.0)],
+                normals: [Vector(0.0, -1.0)],
                 dir,
+                color,
                 dim,
             },
         }
     }
 }
 
-impl Collidable for Wall {
-    fn get_coordinates(&self) -> ObjectCoordinates {
+impl Wall {
+    /// First and last cell `(x1, y1, x2, y2)` taken up by the wall.
+    fn cells(&self) -> (u16, u16, u16, u16) {
+        let GameDimension(rows, columns) = self.dim;
+        let (bottom, right) = (rows.saturating_sub(1), columns.saturating_sub(1));
         match self.dir {
-            Direction::Left => ObjectCoordinates(0, 0, 0, self.dim.0),
-            Direction::Right => ObjectCoordinates(self.dim.1, 0, self.dim.1, self.dim.0),
-            Direction::Top => ObjectCoordinates(0, 0, self.dim.1, 0),
-            Direction::Bottom => ObjectCoordinates(0, self.dim.0, self.dim.1, self.dim.0),
+            Direction::Left => (0, 0, 0, bottom),
+            Direction::Right => (right, 0, right, bottom),
+            Direction::Top => (0, 0, right, 0),
+            Direction::Bottom => (0, rows, right, rows),
         }
     }
-    fn has_collision(&self, other: &dyn Collidable) -> bool {
-        let ObjectCoordinates(ax1, ay1, ax2, ay2) = self.get_coordinates();
-        let ObjectCoordinates(bx1, by1, bx2, by2) = other.get_coordinates();
-
-        let x1 = max(ax1, bx1);
-        let x2 = min(ax2, bx2);
-        let y1 = max(ay1, by1);
-        let y2 = min(ay2, by2);
+}
 
-        if x1 <= x2 && y1 <= y2 {
-            return true;
-        }
-        return false;
+impl Collidable for Wall {
+    fn get_coordinates(&self) -> ObjectCoordinates {
+        let (x1, y1, x2, y2) = self.cells();
+        ObjectCoordinates(x1 as f64, y1 as f64, (x2 + 1) as f64, (y2 + 1) as f64)
     }
-    fn get_normal(&self, _other: &dyn Collidable) -> &super::Vector {
-        // For now, a wall can have only one surface
-        &self.normals[0]
+    fn get_normal(&self, _other: &dyn Collidable) -> Vector {
+        // A wall only has the one surface facing the playfield
+        self.normals[0]
     }
     fn get_velocity(&self) -> super::Vector {
         Vector(0.0, 0.0)
@@ -80,36 +77,29 @@ impl Collidable for Wall {
 }
 
 impl GameObject for Wall {
-    fn fill_object(&self, symbol: style::StyledContent<&str>) -> io::Result<()> {
-        let mut stdout = stdout();
-
-        let ObjectCoordinates(x1, y1, x2, y2) = self.get_coordinates();
+    fn fill_object(
+        &self,
+        canvas: &mut dyn Canvas,
+        symbol: style::StyledContent<&str>,
+    ) -> io::Result<()> {
+        let (x1, y1, x2, y2) = self.cells();
 
         for j in y1..=y2 {
             for i in x1..=x2 {
-                stdout
-                    .queue(cursor::MoveTo(i, j))?
-                    .queue(style::PrintStyledContent(symbol))?;
+                canvas.put(i, j, symbol)?;
             }
         }
-        stdout.flush();
         Ok(())
     }
 
-    fn draw_object(&self) -> io::Result<()> {
+    fn draw_object(&self, canvas: &mut dyn Canvas) -> io::Result<()> {
         match self.dir {
-            Direction::Left => self.fill_object("┃".with(style::Color::Green)),
-            Direction::Right => self.fill_object("┃".with(style::Color::Green)),
-            Direction::Top => self.fill_object("─".with(style::Color::Green)),
-            Direction::Bottom => self.fill_object("─".with(style::Color::Green)),
+            Direction::Left => self.fill_object(canvas, "┃".with(self.color)),
+            Direction::Right => self.fill_object(canvas, "┃".with(self.color)),
+            Direction::Top => self.fill_object(canvas, "─".with(self.color)),
+            Direction::Bottom => self.fill_object(canvas, "─".with(self.color)),
         }
     }
 
-    fn clear_object(&self) -> io::Result<()> {
-        self.fill_object(style(" "))
-    }
-
-    fn move_object(&mut self) -> io::Result<()> {
-        Ok(())
-    }
+    fn move_object(&mut self) {}
 }
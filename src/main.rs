@@ -1,10 +1,45 @@
-mod game;
-
-use game::Game;
+use breakit::game::campaign::Campaign;
+use breakit::game::config::Config;
+use breakit::game::Game;
+use std::env;
 use std::io;
+use std::path::PathBuf;
+use std::process;
+
+/// Print `message` the way the game reports errors and stop.
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("breakit: {}", message);
+    process::exit(1);
+}
 
 fn main() -> io::Result<()> {
-    let mut game = Game::new();
+    // `--config PATH` picks the config file, anything else is a level file
+    // to play in order, instead of the built-in campaign
+    let mut config_path: Option<PathBuf> = None;
+    let mut paths: Vec<String> = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            let path = args.next().unwrap_or_else(|| fail("--config needs a path"));
+            config_path = Some(PathBuf::from(path));
+        } else {
+            paths.push(arg);
+        }
+    }
+
+    let config = match config_path {
+        Some(path) => Config::load(&path),
+        None => Config::load_default(),
+    }
+    .unwrap_or_else(|e| fail(e));
+
+    let campaign = if paths.is_empty() {
+        Campaign::builtin()
+    } else {
+        Campaign::load(&paths).unwrap_or_else(|e| fail(e))
+    };
+
+    let mut game = Game::new(campaign, config)?;
     game.run()?;
     Ok(())
 }